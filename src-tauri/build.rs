@@ -1,3 +1,22 @@
+use std::path::PathBuf;
+
+/// Sentry browser bundle for the webview, from the @sentry/browser package.
+const SENTRY_BUNDLE: &str = "../node_modules/@sentry/browser/build/bundles/bundle.tracing.min.js";
+
 fn main() {
+    // Embedded in the app so the webview doesn't load it from the Sentry CDN.
+    // Without `npm install`, webview error tracking is disabled in debug
+    // builds, and release builds fail so it can't ship disabled.
+    println!("cargo:rerun-if-changed={}", SENTRY_BUNDLE);
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("sentry.bundle.tracing.min.js");
+    let bundle = std::fs::read_to_string(SENTRY_BUNDLE).unwrap_or_else(|_| {
+        if std::env::var("PROFILE").as_deref() == Ok("release") {
+            panic!("@sentry/browser is not installed: run `npm install` before a release build");
+        }
+        println!("cargo:warning=@sentry/browser not installed (npm install), webview error tracking is disabled");
+        String::new()
+    });
+    std::fs::write(out, bundle).unwrap();
+
     tauri_build::build()
 }
@@ -3,35 +3,80 @@
   if (!window.__TAURI__) return;
 
   const { invoke } = window.__TAURI__.core;
-  const { listen } = window.__TAURI__.event;
 
   // Frontend error tracking (GlitchTip / Sentry-compatible).
-  // DSN is read from the same GLITCHTIP_DSN env var via a Tauri command.
-  invoke('get_glitchtip_dsn').then((dsn) => {
-    if (!dsn) return;
+  // DSN and environment come from Tauri (settings, SILEX_GLITCHTIP_DSN or the
+  // built-in GLITCHTIP_DSN), and the Sentry bundle is embedded in the app.
+  // Events go through Tauri before sending: consent categories, scrubbing
+  // of personal data and local recording (telemetry inspector).
+  const filterEvent = (kind) => (event) => invoke('filter_webview_telemetry', { kind, event }).catch(() => null);
+  Promise.all([invoke('get_glitchtip_dsn'), invoke('get_sentry_browser_client')]).then(([dsn, client]) => {
+    if (!dsn || !client) return;
     const script = document.createElement('script');
-    script.src = 'https://browser.sentry-cdn.com/8.46.0/bundle.tracing.min.js';
-    script.crossOrigin = 'anonymous';
-    script.onload = () => {
-      if (window.Sentry) {
-        window.Sentry.init({
-          dsn,
-          release: '0.1.0',
-          environment: 'production',
-          tracesSampleRate: 1.0,
-          integrations: [window.Sentry.browserTracingIntegration()],
-        });
-        window.Sentry.setTag('os', navigator.platform);
-        window.Sentry.setTag('context', 'webview');
-      }
-    };
+    script.textContent = client.bundle;
     document.head.appendChild(script);
-  }).catch(() => { /* GLITCHTIP_DSN not set — telemetry disabled */ });
+    if (window.Sentry) {
+      window.Sentry.init({
+        dsn,
+        release: client.release,
+        environment: client.environment,
+        beforeSend: filterEvent('event'),
+        beforeSendTransaction: filterEvent('transaction'),
+        tracesSampleRate: 1.0,
+        integrations: [window.Sentry.browserTracingIntegration()],
+      });
+      window.Sentry.setTag('os', navigator.platform);
+      window.Sentry.setTag('context', 'webview');
+    }
+  }).catch(() => { /* No DSN configured — telemetry disabled */ });
 
   const TEXT_TAGS = ['p', 'h1', 'h2', 'h3', 'h4', 'h5', 'h6', 'span', 'a', 'li', 'td', 'th', 'label', 'blockquote'];
 
   // Expose debug logging for silex-lib client code
   window.__silexDebug = (msg) => invoke('log_debug', { message: msg });
+
+  // Forward console output and uncaught errors to Rust (log file and the
+  // get_console_logs MCP tool). Batched here, rate-limited in Rust.
+  const consoleQueue = [];
+  let consoleTimer = null;
+  const forwardConsole = (entry) => {
+    consoleQueue.push(entry);
+    if (consoleTimer) return;
+    consoleTimer = setTimeout(() => {
+      consoleTimer = null;
+      invoke('log_console', { entries: consoleQueue.splice(0) }).catch(() => {});
+    }, 250);
+  };
+  const formatArg = (arg) => {
+    if (typeof arg === 'string') return arg;
+    if (arg instanceof Error) return String(arg);
+    try { return JSON.stringify(arg) ?? String(arg); } catch { return String(arg); }
+  };
+  ['error', 'warn', 'info', 'log', 'debug'].forEach((level) => {
+    const original = console[level].bind(console);
+    console[level] = (...args) => {
+      original(...args);
+      const error = args.find((arg) => arg instanceof Error);
+      forwardConsole({ level, message: args.map(formatArg).join(' '), stack: error?.stack ?? null });
+    };
+  });
+  window.addEventListener('error', (e) => {
+    forwardConsole({
+      level: 'error',
+      message: e.message || formatArg(e.error),
+      source: e.filename || null,
+      line: e.lineno || null,
+      column: e.colno || null,
+      stack: e.error?.stack ?? null,
+    });
+  });
+  window.addEventListener('unhandledrejection', (e) => {
+    forwardConsole({
+      level: 'error',
+      message: 'Unhandled promise rejection: ' + formatArg(e.reason),
+      stack: e.reason?.stack ?? null,
+    });
+  });
   invoke('log_debug', { message: '[bridge] desktop-bridge loaded, page=' + window.location.href });
 
   // MCP helpers (available globally for eval_js calls)
@@ -205,6 +250,128 @@
       return newId;
     },
 
+    // Files the publication would write: runs the editor's publication
+    // (same transformers and settings) but answers its requests to the
+    // publication API here, with a finished job, so nothing is sent to the
+    // hosting. The editor sees a successful publication; its dialog is
+    // closed and the publish events are ignored (no span, no live reload)
+    // until the editor reports the end of this run.
+    dryRunPublication(editor) {
+      return new Promise((resolve, reject) => {
+        const originalFetch = window.fetch;
+        const job = { jobId: `dry-run-${Date.now()}`, status: 'success', message: 'Dry run, nothing was published' };
+        const answer = (body) => new Response(JSON.stringify(body), {
+          status: 200,
+          headers: { 'Content-Type': 'application/json' },
+        });
+        let files = null;
+        let failure = null;
+        const settle = (message) => {
+          if (files) resolve(files);
+          else reject(failure ?? new Error(message));
+        };
+        const finish = () => {
+          clearTimeout(timer);
+          window.fetch = originalFetch;
+          editor.off('silex:publish:end', onEnd);
+          editor.off('silex:publish:error', onError);
+          editor.__silexDryRun = false;
+          editor.stopCommand('publish');
+        };
+        const onEnd = (data) => {
+          finish();
+          settle(data?.message ?? 'The publication did not reach the publication API');
+        };
+        const onError = (data) => {
+          finish();
+          settle(data?.message ?? String(data ?? 'Publication failed'));
+        };
+        const timer = setTimeout(() => {
+          finish();
+          settle('The publication did not reach the publication API');
+        }, 30000);
+        const typeOf = (path) => {
+          if (/\.html?$/i.test(path)) return 'html';
+          if (/\.css$/i.test(path)) return 'css';
+          if (/\.m?js$/i.test(path)) return 'js';
+          return 'asset';
+        };
+        window.fetch = async (input, init) => {
+          const url = new URL(typeof input === 'string' ? input : input.url, window.location.origin);
+          const method = (init?.method ?? (typeof input === 'string' ? 'GET' : input.method)).toUpperCase();
+          if (!url.pathname.startsWith('/api/publication')) {
+            return originalFetch(input, init);
+          }
+          // Job status polling
+          if (method === 'GET' && url.searchParams.get('jobId') === job.jobId) {
+            return answer(job);
+          }
+          if (method !== 'POST') {
+            return originalFetch(input, init);
+          }
+          try {
+            const data = JSON.parse(typeof init?.body === 'string' ? init.body : await new Request(input, init).text());
+            files = (data.files ?? []).map(f => ({ path: f.path, type: typeOf(f.path) }));
+          } catch (err) {
+            failure = new Error(`Unexpected publication request: ${err.message || err}`);
+          }
+          return answer({ url: null, job });
+        };
+        editor.__silexDryRun = true;
+        editor.on('silex:publish:end', onEnd);
+        editor.on('silex:publish:error', onError);
+        try {
+          editor.runCommand('publish');
+        } catch (err) {
+          finish();
+          reject(err);
+        }
+      });
+    },
+
+    // Run the editor's publication and post its events to progressUrl.
+    // Resolves with { success, url, message } once publication ends or fails.
+    publish(editor, progressUrl) {
+      return new Promise((resolve) => {
+        const post = (type, data) => fetch(window.location.origin + progressUrl, {
+          method: 'POST',
+          headers: { 'Content-Type': 'application/json' },
+          body: JSON.stringify({ type, message: data?.message ?? type, total: data?.total ?? null }),
+        }).catch(() => { /* progress is best effort */ });
+        const events = ['start', 'page', 'data', 'end', 'error'].map(e => `silex:publish:${e}`);
+        const handlers = {};
+        const cleanup = () => events.forEach(e => editor.off(e, handlers[e]));
+        // Fail fast when the editor doesn't start publishing (no hosting set up...)
+        const startTimer = setTimeout(() => {
+          cleanup();
+          resolve({ success: false, url: null, message: 'The publication did not start' });
+        }, 15000);
+        events.forEach((e) => {
+          handlers[e] = (data) => {
+            const type = e.split(':').pop();
+            clearTimeout(startTimer);
+            post(type, data);
+            if (type === 'end' || type === 'error') {
+              cleanup();
+              resolve({
+                success: type === 'end' && data?.success !== false,
+                url: data?.url ?? data?.publicationUrl ?? null,
+                message: data?.message ?? (type === 'error' ? String(data ?? 'Publication failed') : null),
+              });
+            }
+          };
+          editor.on(e, handlers[e]);
+        });
+        try {
+          editor.runCommand('publish');
+        } catch (err) {
+          clearTimeout(startTimer);
+          cleanup();
+          resolve({ success: false, url: null, message: err.message || String(err) });
+        }
+      });
+    },
+
     // Query capabilities registry and return as JSON-serializable tool definitions
     getCapabilities() {
       const caps = window.grapesjsAiCapabilities;
@@ -250,6 +417,26 @@
     }
   });
 
+  // Drop overlay: Tauri handles file drops, it sends what dropping will do
+  const currentWindow = window.__TAURI__.webviewWindow.getCurrentWebviewWindow();
+  let dropOverlay = null;
+  currentWindow.listen('drag-drop', ({ payload }) => {
+    if (payload.state === 'leave' || payload.state === 'done') {
+      dropOverlay?.remove();
+      dropOverlay = null;
+      return;
+    }
+    if (!dropOverlay) {
+      dropOverlay = document.createElement('div');
+      dropOverlay.style.cssText = 'position:fixed;inset:0;z-index:2147483647;pointer-events:none;display:flex;'
+        + 'align-items:center;justify-content:center;font:600 20px system-ui,sans-serif;color:#fff;'
+        + 'border:4px dashed currentColor;box-sizing:border-box;';
+      document.body.appendChild(dropOverlay);
+    }
+    dropOverlay.style.background = payload.accepted ? 'rgba(40,100,200,0.6)' : 'rgba(160,40,40,0.6)';
+    dropOverlay.textContent = payload.message ?? '';
+  });
+
   const params = new URLSearchParams(window.location.search);
   const websiteId = params.get('id');
 
@@ -286,37 +473,74 @@
         });
       });
 
-    // Track unsaved changes
-    editor.on('change:changesCount', () => invoke('mark_unsaved'));
+    // Track unsaved changes (the count is reset to 0 after each save)
+    editor.on('change:changesCount', () => {
+      if (editor.getDirtyCount() > 0) invoke('mark_unsaved');
+    });
 
     // Track project_save
     editor.on('storage:start:store', () => {
       editor.__saveSpan = window.Sentry?.startInactiveSpan?.({ name: 'project_save', op: 'lifecycle', forceTransaction: true });
+      editor.__dirtyAtStore = editor.getDirtyCount();
     });
+    // Crash recovery: push unsaved project data to Tauri every 30s while dirty
+    setInterval(() => {
+      if (editor.getDirtyCount() > 0) {
+        invoke('write_recovery', { data: editor.getProjectData() })
+          .catch((e) => window.__silexDebug?.('[bridge] write_recovery failed: ' + e));
+      }
+    }, 30000);
+
+    // Report save results to Tauri. The project is clean only if nothing
+    // changed while saving (the count is reset once the save is done, or
+    // still the one it started with).
     editor.on('storage:end:store', () => {
       editor.__saveSpan?.end();
       editor.__saveSpan = null;
+      const dirty = editor.getDirtyCount();
+      invoke('report_save_result', { success: true, clean: dirty === 0 || dirty <= (editor.__dirtyAtStore ?? 0) });
     });
-    editor.on('storage:error:store', () => {
+    editor.on('storage:error:store', (err) => {
       if (editor.__saveSpan) { editor.__saveSpan.setStatus({ code: 2, message: 'internal_error' }); editor.__saveSpan.end(); editor.__saveSpan = null; }
+      invoke('report_save_result', { success: false, error: err?.message || String(err) });
     });
 
     // Track project_publish
     editor.on('silex:publish:start', () => {
+      if (editor.__silexDryRun) return;
       editor.__publishSpan = window.Sentry?.startInactiveSpan?.({ name: 'project_publish', op: 'lifecycle', forceTransaction: true });
     });
-    editor.on('silex:publish:end', () => {
+    editor.on('silex:publish:end', (data) => {
       editor.__publishSpan?.end();
       editor.__publishSpan = null;
+      // Live reload for the local preview server
+      if (data?.success !== false && !editor.__silexDryRun) invoke('notify_published', { websiteId });
     });
     editor.on('silex:publish:error', () => {
       if (editor.__publishSpan) { editor.__publishSpan.setStatus({ code: 2, message: 'internal_error' }); editor.__publishSpan.end(); editor.__publishSpan = null; }
     });
 
-    // Listen for menu events from Tauri (triggered by MCP or quit dialog)
-    listen('menu-save', () => editor.store());
-    listen('menu-undo', () => editor.UndoManager.undo());
-    listen('menu-redo', () => editor.UndoManager.redo());
-    listen('menu-close-project', () => { window.location.href = '/'; });
+    // Files dropped on the window: assets uploaded by Tauri, HTML files as new pages
+    currentWindow.listen('add-assets', ({ payload }) => {
+      editor.AssetManager.add(payload);
+    });
+    currentWindow.listen('import-page', ({ payload }) => {
+      const page = editor.Pages.add({ name: payload.name, component: payload.html });
+      if (page) editor.Pages.select(page);
+    });
+
+    // Listen for menu events from Tauri (native menu, MCP or quit dialog).
+    // Listen on this window only: menu events target the focused editor window.
+    // A save requested with a token (closing a window) is answered with the
+    // result of the store it starts here, not of one already running
+    currentWindow.listen('menu-save', ({ payload }) => {
+      const token = payload?.token ?? null;
+      const saving = Promise.resolve().then(() => editor.store());
+      if (token === null) return;
+      saving
+        .then(() => invoke('report_save_result', { token, success: true }))
+        .catch((err) => invoke('report_save_result', { token, success: false, error: err?.message || String(err) }));
+    });
+    currentWindow.listen('menu-close-project', () => { window.location.href = '/'; });
   });
 })();
@@ -0,0 +1,132 @@
+/*
+ * Website archives: a zip of a site's storage folder, to move sites between
+ * machines or keep backups. Importing an archive creates a new site.
+ *
+ * Layout: silex-website.json (source website id and name) at the root,
+ * next to the files of the storage folder (website.json, assets/, ...).
+ */
+
+use std::io::{Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::templates::{delete_website, rewrite_website_id};
+
+const MANIFEST: &str = "silex-website.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    website_id: String,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+fn add_dir<W: Write + std::io::Seek>(
+    zip: &mut zip::ZipWriter<W>,
+    dir: &Path,
+    prefix: &str,
+) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        let path = entry.path();
+        if path.is_dir() {
+            add_dir(zip, &path, &format!("{}/", name))?;
+        } else {
+            let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .map_err(|e| format!("Failed to write archive: {}", e))?;
+            zip.write_all(&bytes)
+                .map_err(|e| format!("Failed to write archive: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Write a website's storage folder to a zip archive.
+pub fn export_website(app: &tauri::AppHandle, website_id: &str, dest: &Path) -> Result<(), String> {
+    let site_dir = crate::websites::site_dir(app, website_id)?;
+    if !site_dir.is_dir() {
+        return Err(format!("Website '{}' not found in storage", website_id));
+    }
+    let name = std::fs::read_to_string(site_dir.join("meta.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .and_then(|v| v.get("name").and_then(|n| n.as_str()).map(String::from));
+
+    let file = std::fs::File::create(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let manifest = Manifest { website_id: website_id.to_string(), name };
+    zip.start_file(MANIFEST, zip::write::SimpleFileOptions::default())
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+    zip.write_all(serde_json::to_string_pretty(&manifest).unwrap_or_default().as_bytes())
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+    add_dir(&mut zip, &site_dir, "")?;
+    zip.finish().map_err(|e| format!("Failed to write archive: {}", e))?;
+
+    tracing::info!("Exported website {} to {}", website_id, dest.display());
+    Ok(())
+}
+
+/// Whether a file looks like a website archive (zip with a Silex manifest).
+pub fn is_website_archive(path: &Path) -> bool {
+    std::fs::File::open(path)
+        .ok()
+        .and_then(|f| zip::ZipArchive::new(f).ok())
+        .is_some_and(|mut zip| zip.by_name(MANIFEST).is_ok())
+}
+
+/// Create a new website from an archive. Returns the new website id.
+pub async fn import_website(app: &tauri::AppHandle, base_url: &str, archive: &Path) -> Result<String, String> {
+    let file = std::fs::File::open(archive).map_err(|e| format!("Failed to open {}: {}", archive.display(), e))?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Not a website archive: {}", e))?;
+
+    let manifest: Manifest = {
+        let mut entry = zip
+            .by_name(MANIFEST)
+            .map_err(|_| "Not a website archive: missing silex-website.json".to_string())?;
+        let mut raw = String::new();
+        entry.read_to_string(&mut raw).map_err(|e| format!("Invalid archive: {}", e))?;
+        serde_json::from_str(&raw).map_err(|e| format!("Invalid archive manifest: {}", e))?
+    };
+
+    crate::websites::validate_website_id(&manifest.website_id)
+        .map_err(|e| format!("Invalid archive manifest: {}", e))?;
+
+    let website_id = crate::templates::create_website(base_url, manifest.name.as_deref()).await?;
+    let extracted = crate::websites::site_dir(app, &website_id).and_then(|site_dir| {
+        extract(&mut zip, &site_dir)?;
+        rewrite_website_id(&site_dir, &manifest.website_id, &website_id)
+            .map_err(|e| format!("Failed to finalize imported website: {}", e))
+    });
+    if let Err(e) = extracted {
+        // Don't leave a half imported site behind
+        delete_website(base_url, &website_id).await;
+        return Err(e);
+    }
+
+    tracing::info!("Imported {} as website {}", archive.display(), website_id);
+    Ok(website_id)
+}
+
+/// Write the archive's files into a site folder.
+fn extract(zip: &mut zip::ZipArchive<std::fs::File>, site_dir: &Path) -> Result<(), String> {
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).map_err(|e| format!("Invalid archive: {}", e))?;
+        // enclosed_name() rejects absolute paths and ".." components
+        let Some(relative) = entry.enclosed_name() else { continue };
+        // Keep the meta.json written by the server for the new site
+        if relative == Path::new(MANIFEST) || relative == Path::new("meta.json") || entry.is_dir() {
+            continue;
+        }
+        let target = site_dir.join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to import: {}", e))?;
+        }
+        let mut out = std::fs::File::create(&target).map_err(|e| format!("Failed to import: {}", e))?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| format!("Failed to import: {}", e))?;
+    }
+    Ok(())
+}
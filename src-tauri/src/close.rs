@@ -0,0 +1,218 @@
+/*
+ * Closing an editor window, or its project (File > Close Project), when
+ * it has unsaved changes.
+ *
+ * "Save & Close" emits menu-save to the window with a token, then waits
+ * for the result of that save, reported by the bridge with the same token
+ * (report_save_result command) while a small "Saving..."
+ * window is shown. The editor window is only destroyed, or sent back to
+ * the dashboard, once the save succeeded; a failed save offers to retry,
+ * close anyway or cancel. Closing without saving also drops the crash
+ * recovery journal entry.
+ */
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult};
+use tokio::sync::oneshot;
+
+/// A save that takes longer than this is reported as failed.
+const SAVE_TIMEOUT: Duration = Duration::from_secs(60);
+
+const SAVE_AND_CLOSE: &str = "Save & Close";
+const DONT_SAVE: &str = "Don't Save";
+const RETRY: &str = "Retry";
+const CLOSE_ANYWAY: &str = "Close Anyway";
+const CANCEL: &str = "Cancel";
+
+type SaveResult = oneshot::Sender<Result<(), String>>;
+
+/// Save results awaited by the close flow, by window label and save token.
+/// Only the save started for a request settles it: a save already running
+/// (autosave) may miss the latest changes.
+#[derive(Default)]
+pub struct SaveWaiters {
+    next_token: AtomicU64,
+    waiters: Mutex<HashMap<String, HashMap<u64, SaveResult>>>,
+}
+
+impl SaveWaiters {
+    fn wait(&self, label: &str) -> (u64, oneshot::Receiver<Result<(), String>>) {
+        let token = self.next_token.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.waiters.lock().unwrap().entry(label.to_string()).or_default().insert(token, tx);
+        (token, rx)
+    }
+
+    fn forget(&self, label: &str, token: u64) {
+        let mut waiters = self.waiters.lock().unwrap();
+        if let Some(window) = waiters.get_mut(label) {
+            window.remove(&token);
+            if window.is_empty() {
+                waiters.remove(label);
+            }
+        }
+    }
+
+    /// Called with the result of the save a window's bridge ran for `token`.
+    pub fn resolve(&self, label: &str, token: u64, result: Result<(), String>) {
+        let tx = self.waiters.lock().unwrap().get_mut(label).and_then(|w| w.remove(&token));
+        self.forget(label, token);
+        if let Some(tx) = tx {
+            let _ = tx.send(result);
+        }
+    }
+}
+
+/// What is closed once the changes are saved or dropped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Closing {
+    /// The editor window.
+    Window,
+    /// The project: the window goes back to the dashboard.
+    Project,
+}
+
+/// Ask what to do with unsaved changes before closing a window or its project.
+pub fn confirm_close(window: &tauri::WebviewWindow, website_name: Option<&str>, closing: Closing) {
+    let message = match website_name {
+        Some(name) => format!("Do you want to save changes to \"{}\" before closing?", name),
+        None => "Do you want to save changes before closing?".to_string(),
+    };
+    let name = website_name.map(String::from);
+    let window = window.clone();
+    window
+        .dialog()
+        .message(message)
+        .title("Silex")
+        .kind(MessageDialogKind::Warning)
+        .parent(&window)
+        .buttons(MessageDialogButtons::YesNoCancelCustom(
+            SAVE_AND_CLOSE.into(),
+            DONT_SAVE.into(),
+            CANCEL.into(),
+        ))
+        .show_with_result(move |result| match result {
+            MessageDialogResult::Yes => save_and_close(window, name, closing),
+            MessageDialogResult::Custom(s) if s == SAVE_AND_CLOSE => save_and_close(window, name, closing),
+            MessageDialogResult::No => discard_and_close(&window, closing),
+            MessageDialogResult::Custom(s) if s == DONT_SAVE => discard_and_close(&window, closing),
+            _ => {}
+        });
+}
+
+/// Close without saving: the changes are deliberately dropped, so they
+/// must not be offered for recovery at next launch.
+fn discard_and_close(window: &tauri::WebviewWindow, closing: Closing) {
+    let app = window.app_handle();
+    if let Some(website_id) = app.state::<crate::AppState>().project(window.label()).website_id {
+        crate::recovery::clear(app, &website_id);
+    }
+    close(window, closing);
+}
+
+fn close(window: &tauri::WebviewWindow, closing: Closing) {
+    match closing {
+        Closing::Window => {
+            let _ = window.destroy();
+        }
+        // The bridge leaves the editor for the dashboard
+        Closing::Project => {
+            let _ = window.emit_to(window.label(), "menu-close-project", ());
+        }
+    }
+}
+
+/// Save a window's project and wait for the editor's result.
+pub async fn save(window: &tauri::WebviewWindow) -> Result<(), String> {
+    let waiters = window.app_handle().state::<SaveWaiters>();
+    let (token, rx) = waiters.wait(window.label());
+    let payload = serde_json::json!({ "token": token });
+    let result = match window.emit_to(window.label(), "menu-save", payload) {
+        Ok(()) => match tokio::time::timeout(SAVE_TIMEOUT, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err("The editor stopped responding".to_string()),
+            Err(_) => Err(format!("Saving took more than {} seconds", SAVE_TIMEOUT.as_secs())),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+    waiters.forget(window.label(), token);
+    result
+}
+
+/// Save, wait for the result, then close the window or report the failure.
+fn save_and_close(window: tauri::WebviewWindow, name: Option<String>, closing: Closing) {
+    tauri::async_runtime::spawn(async move {
+        let app = window.app_handle().clone();
+        let progress = show_progress(&window, name.as_deref());
+
+        let result = save(&window).await;
+
+        if let Some(progress) = progress {
+            let _ = progress.destroy();
+        }
+        match result {
+            Ok(()) => close(&window, closing),
+            Err(e) => {
+                tracing::warn!("Save before closing {} failed: {}", window.label(), e);
+                show_save_failed(window, name, e, closing);
+            }
+        }
+    });
+}
+
+fn show_save_failed(window: tauri::WebviewWindow, name: Option<String>, error: String, closing: Closing) {
+    let message = format!(
+        "{} could not be saved:\n{}\n\nIf you close anyway, unsaved changes will be lost.",
+        name.as_deref().map(|n| format!("\"{}\"", n)).unwrap_or_else(|| "The website".into()),
+        error
+    );
+    let dialog_window = window.clone();
+    dialog_window
+        .dialog()
+        .message(message)
+        .title("Save Failed")
+        .kind(MessageDialogKind::Error)
+        .parent(&dialog_window)
+        .buttons(MessageDialogButtons::YesNoCancelCustom(
+            RETRY.into(),
+            CLOSE_ANYWAY.into(),
+            CANCEL.into(),
+        ))
+        .show_with_result(move |result| match result {
+            MessageDialogResult::Yes => save_and_close(window, name, closing),
+            MessageDialogResult::Custom(s) if s == RETRY => save_and_close(window, name, closing),
+            MessageDialogResult::No => discard_and_close(&window, closing),
+            MessageDialogResult::Custom(s) if s == CLOSE_ANYWAY => discard_and_close(&window, closing),
+            _ => {}
+        });
+}
+
+/// Small "Saving..." window on top of the editor window.
+fn show_progress(window: &tauri::WebviewWindow, name: Option<&str>) -> Option<tauri::WebviewWindow> {
+    let label = format!("saving-{}", window.label());
+    let page = match name {
+        Some(name) => format!(
+            "saving.html?name={}",
+            url::form_urlencoded::byte_serialize(name.as_bytes()).collect::<String>()
+        ),
+        None => "saving.html".to_string(),
+    };
+    let builder = WebviewWindowBuilder::new(window.app_handle(), &label, WebviewUrl::App(page.into()))
+        .title("Saving")
+        .inner_size(320.0, 72.0)
+        .resizable(false)
+        .decorations(false)
+        .skip_taskbar(true)
+        .always_on_top(true)
+        .center();
+    builder
+        .parent(window)
+        .and_then(|b| b.build())
+        .map_err(|e| tracing::warn!("Failed to show save progress: {}", e))
+        .ok()
+}
@@ -0,0 +1,166 @@
+/*
+ * Webview console forwarding.
+ *
+ * The bridge forwards console output (console.error/warn/info/log/debug,
+ * including __silexMcp._log) and uncaught errors and promise rejections of
+ * editor windows, with their source URL, line and stack. Entries are
+ * logged with the "webview" target at the matching tracing level, and the
+ * most recent ones are kept for the MCP get_console_logs tool, so agents
+ * can see the JS errors their actions cause.
+ *
+ * A window sending more than MAX_PER_SECOND entries in a second has the
+ * rest dropped; the number dropped is logged once it calms down.
+ */
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::logs::WEBVIEW_TARGET;
+
+/// Entries kept for get_console_logs, all windows together.
+const MAX_ENTRIES: usize = 500;
+const MAX_PER_SECOND: u32 = 50;
+/// Longest message kept, stacks included.
+const MAX_MESSAGE_LENGTH: usize = 4000;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleLevel {
+    Error,
+    Warn,
+    Info,
+    Log,
+    Debug,
+}
+
+/// A console message or uncaught error, as sent by the bridge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleEntry {
+    pub level: ConsoleLevel,
+    pub message: String,
+    pub source: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub stack: Option<String>,
+    /// Set on reception.
+    #[serde(default)]
+    pub window: String,
+    /// Milliseconds since the epoch, set on reception.
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+struct RateWindow {
+    start: Instant,
+    count: u32,
+    dropped: u32,
+}
+
+#[derive(Default)]
+pub struct ConsoleLog {
+    entries: Mutex<VecDeque<ConsoleEntry>>,
+    rates: Mutex<HashMap<String, RateWindow>>,
+}
+
+fn truncate(text: &mut String) {
+    if text.len() > MAX_MESSAGE_LENGTH {
+        let mut end = MAX_MESSAGE_LENGTH;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push('\u{2026}');
+    }
+}
+
+impl ConsoleLog {
+    /// Whether an entry of `window` gets through the rate limit.
+    fn allow(&self, window: &str) -> bool {
+        let mut rates = self.rates.lock().unwrap();
+        let rate = rates.entry(window.to_string()).or_insert(RateWindow {
+            start: Instant::now(),
+            count: 0,
+            dropped: 0,
+        });
+        if rate.start.elapsed() >= Duration::from_secs(1) {
+            if rate.dropped > 0 {
+                tracing::warn!(target: WEBVIEW_TARGET, "[{}] {} console messages dropped (rate limit)", window, rate.dropped);
+            }
+            *rate = RateWindow { start: Instant::now(), count: 0, dropped: 0 };
+        }
+        rate.count += 1;
+        if rate.count > MAX_PER_SECOND {
+            rate.dropped += 1;
+            return false;
+        }
+        true
+    }
+
+    pub fn record(&self, window: &str, mut entry: ConsoleEntry) {
+        if !self.allow(window) {
+            return;
+        }
+        truncate(&mut entry.message);
+        if let Some(stack) = entry.stack.as_mut() {
+            truncate(stack);
+        }
+        entry.window = window.to_string();
+        entry.timestamp = crate::unix_time().as_millis() as u64;
+
+        let location = match (&entry.source, entry.line) {
+            (Some(source), Some(line)) => format!(" ({}:{})", source, line),
+            (Some(source), None) => format!(" ({})", source),
+            _ => String::new(),
+        };
+        let stack = entry.stack.as_deref().map(|s| format!("\n{}", s)).unwrap_or_default();
+        match entry.level {
+            ConsoleLevel::Error => tracing::error!(target: WEBVIEW_TARGET, "[{}] {}{}{}", window, entry.message, location, stack),
+            ConsoleLevel::Warn => tracing::warn!(target: WEBVIEW_TARGET, "[{}] {}{}{}", window, entry.message, location, stack),
+            ConsoleLevel::Info | ConsoleLevel::Log => {
+                tracing::info!(target: WEBVIEW_TARGET, "[{}] {}{}", window, entry.message, location)
+            }
+            ConsoleLevel::Debug => tracing::debug!(target: WEBVIEW_TARGET, "[{}] {}{}", window, entry.message, location),
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Recent entries, oldest first: at least as severe as `level`, of
+    /// `window` if given, newer than `since` (milliseconds since the epoch).
+    pub fn recent(&self, window: Option<&str>, level: ConsoleLevel, since: Option<u64>, limit: usize) -> Vec<ConsoleEntry> {
+        let entries = self.entries.lock().unwrap();
+        let mut recent: Vec<ConsoleEntry> = entries
+            .iter()
+            .rev()
+            .filter(|e| e.level <= level)
+            .filter(|e| window.is_none_or(|w| e.window == w))
+            .filter(|e| since.is_none_or(|s| e.timestamp > s))
+            .take(limit)
+            .cloned()
+            .collect();
+        recent.reverse();
+        recent
+    }
+
+    /// Forget the rate limit of a closed window (its entries stay).
+    pub fn forget_window(&self, window: &str) {
+        self.rates.lock().unwrap().remove(window);
+    }
+}
+
+/// Record entries forwarded by a window's bridge.
+pub fn record(app: &tauri::AppHandle, window: &str, entries: Vec<ConsoleEntry>) {
+    let log = app.state::<ConsoleLog>();
+    for entry in entries {
+        log.record(window, entry);
+    }
+}
@@ -0,0 +1,240 @@
+/*
+ * Files dropped onto an editor window.
+ *
+ *   images, fonts    uploaded to the open site's assets (server asset API),
+ *                    then added to the editor's asset manager by the bridge
+ *   website archive  imported as a new site and opened (same checks and
+ *                    cleanup as File > Import, see archive::import_website)
+ *   HTML file        offered to import as a new page of the open site
+ *
+ * While files are dragged over a window, the bridge shows an overlay with
+ * what dropping them will do ("drag-drop" events).
+ */
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{DragDropEvent, Emitter, Manager};
+
+use crate::AppState;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "svg", "webp", "avif", "ico", "bmp"];
+const FONT_EXTENSIONS: &[&str] = &["woff", "woff2", "ttf", "otf", "eot"];
+const HTML_EXTENSIONS: &[&str] = &["html", "htm"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DropKind {
+    Asset,
+    Archive,
+    Html,
+    Unsupported,
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn kind(path: &Path) -> DropKind {
+    let ext = extension(path);
+    if IMAGE_EXTENSIONS.contains(&ext.as_str()) || FONT_EXTENSIONS.contains(&ext.as_str()) {
+        DropKind::Asset
+    } else if HTML_EXTENSIONS.contains(&ext.as_str()) {
+        DropKind::Html
+    } else if ext == "zip" && crate::archive::is_website_archive(path) {
+        DropKind::Archive
+    } else {
+        DropKind::Unsupported
+    }
+}
+
+/// Overlay state sent to the bridge.
+#[derive(Serialize, Clone)]
+struct DragDropState {
+    /// "enter", "leave", "busy" or "done".
+    state: &'static str,
+    message: Option<String>,
+    /// Whether dropping does something.
+    accepted: bool,
+}
+
+fn emit_state(window: &tauri::WebviewWindow, state: &'static str, message: Option<String>, accepted: bool) {
+    let _ = window.emit_to(window.label(), "drag-drop", DragDropState { state, message, accepted });
+}
+
+/// What dropping `paths` on this window will do, or why it can't.
+fn describe(paths: &[PathBuf], project_open: bool) -> Result<String, String> {
+    let kinds: Vec<DropKind> = paths.iter().map(|p| kind(p)).collect();
+    let count = |k: DropKind| kinds.iter().filter(|&&x| x == k).count();
+    if paths.is_empty() || count(DropKind::Unsupported) == kinds.len() {
+        return Err("Only images, fonts, HTML files and website archives can be dropped".into());
+    }
+    let mut actions = Vec::new();
+    let archives = count(DropKind::Archive);
+    if archives > 0 {
+        actions.push(if archives == 1 { "import 1 website".to_string() } else { format!("import {} websites", archives) });
+    }
+    let needs_project = count(DropKind::Asset) + count(DropKind::Html) > 0;
+    if needs_project && !project_open {
+        if archives == 0 {
+            return Err("Open a website to add images, fonts or pages".into());
+        }
+    } else {
+        let assets = count(DropKind::Asset);
+        if assets > 0 {
+            actions.push(if assets == 1 { "add 1 asset".to_string() } else { format!("add {} assets", assets) });
+        }
+        let pages = count(DropKind::Html);
+        if pages > 0 {
+            actions.push(if pages == 1 { "import 1 page".to_string() } else { format!("import {} pages", pages) });
+        }
+    }
+    let mut message = actions.join(", ");
+    if let Some(first) = message.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+    Ok(message)
+}
+
+/// Handle a drag-drop event of an editor window.
+pub fn handle(window: &tauri::WebviewWindow, event: &DragDropEvent) {
+    let project = window.app_handle().state::<AppState>().project(window.label());
+    match event {
+        DragDropEvent::Enter { paths, .. } => match describe(paths, project.website_id.is_some()) {
+            Ok(message) => emit_state(window, "enter", Some(message), true),
+            Err(reason) => emit_state(window, "enter", Some(reason), false),
+        },
+        DragDropEvent::Leave => emit_state(window, "leave", None, false),
+        DragDropEvent::Drop { paths, .. } => {
+            if let Err(reason) = describe(paths, project.website_id.is_some()) {
+                emit_state(window, "done", None, false);
+                crate::menu::show_error(window.app_handle(), "Import", reason);
+                return;
+            }
+            emit_state(window, "busy", Some("Importing\u{2026}".into()), true);
+            let window = window.clone();
+            let paths = paths.clone();
+            tauri::async_runtime::spawn(async move {
+                let errors = import(&window, project.website_id.as_deref(), paths).await;
+                emit_state(&window, "done", None, true);
+                if !errors.is_empty() {
+                    crate::menu::show_error(window.app_handle(), "Import", errors.join("\n"));
+                }
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Import dropped files, returning one message per failure.
+async fn import(window: &tauri::WebviewWindow, website_id: Option<&str>, paths: Vec<PathBuf>) -> Vec<String> {
+    let app = window.app_handle();
+    let mut errors = Vec::new();
+    let mut assets = Vec::new();
+    for path in paths {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        match (kind(&path), website_id) {
+            (DropKind::Archive, _) => {
+                let result = crate::archive::import_website(app, &crate::base_url(app), &path).await;
+                if let Err(e) = result.and_then(|id| crate::launch::open_website(app, &id)) {
+                    errors.push(format!("{}: {}", name, e));
+                }
+            }
+            (DropKind::Asset, Some(_)) => assets.push(path),
+            (DropKind::Html, Some(_)) => confirm_import_page(window, &path),
+            (DropKind::Asset | DropKind::Html, None) => {
+                errors.push(format!("{}: open a website first", name));
+            }
+            (DropKind::Unsupported, _) if extension(&path) == "zip" => {
+                errors.push(format!("{}: not a Silex website archive", name));
+            }
+            (DropKind::Unsupported, _) => errors.push(format!("{}: unsupported file type", name)),
+        }
+    }
+    if let (Some(website_id), false) = (website_id, assets.is_empty()) {
+        match upload_assets(app, website_id, &assets).await {
+            Ok(urls) => {
+                let _ = window.emit_to(window.label(), "add-assets", urls);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    errors
+}
+
+/// Upload files through the server asset API, returning their asset URLs.
+async fn upload_assets(app: &tauri::AppHandle, website_id: &str, paths: &[PathBuf]) -> Result<Vec<String>, String> {
+    let mut form = reqwest::multipart::Form::new();
+    for path in paths {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        let mime = mime_guess::from_path(path).first_or_octet_stream();
+        let part = reqwest::multipart::Part::bytes(bytes)
+            .file_name(name)
+            .mime_str(mime.as_ref())
+            .map_err(|e| e.to_string())?;
+        form = form.part("files[]", part);
+    }
+    let url = format!(
+        "{}/api/website/assets?websiteId={}&connectorId=fs-storage",
+        crate::base_url(app),
+        url::form_urlencoded::byte_serialize(website_id.as_bytes()).collect::<String>()
+    );
+    let resp = reqwest::Client::new()
+        .post(&url)
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| format!("Error uploading assets: {}", e))?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(format!("Error uploading assets ({}): {}", status, body));
+    }
+    let body: serde_json::Value = resp.json().await.map_err(|e| format!("Invalid upload response: {}", e))?;
+    let urls = body
+        .get("data")
+        .and_then(|d| d.as_array())
+        .map(|a| a.iter().filter_map(|u| u.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    tracing::info!("Uploaded {} assets to website {}", paths.len(), website_id);
+    Ok(urls)
+}
+
+/// Page to create in the editor from a dropped HTML file.
+#[derive(Serialize, Clone)]
+struct ImportPage {
+    name: String,
+    html: String,
+}
+
+/// Ask before adding a dropped HTML file as a new page.
+fn confirm_import_page(window: &tauri::WebviewWindow, path: &Path) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    let html = match std::fs::read_to_string(path) {
+        Ok(html) => html,
+        Err(e) => {
+            crate::menu::show_error(window.app_handle(), "Import Page", format!("Failed to read {}: {}", path.display(), e));
+            return;
+        }
+    };
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Imported page".into());
+    let target = window.clone();
+    window
+        .dialog()
+        .message(format!("Import \"{}\" as a new page of this website?", name))
+        .title("Import as Page")
+        .kind(MessageDialogKind::Info)
+        .parent(window)
+        .buttons(MessageDialogButtons::OkCancelCustom("Import as Page".into(), "Cancel".into()))
+        .show(move |accepted| {
+            if accepted {
+                let _ = target.emit_to(target.label(), "import-page", ImportPage { name, html });
+            }
+        });
+}
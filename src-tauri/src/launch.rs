@@ -0,0 +1,289 @@
+/*
+ * Launch arguments, from the command line or forwarded by a second
+ * instance (the single-instance plugin exits it and calls
+ * `handle_second_instance` in the running app):
+ *   --open <website_id>   open a website in the editor
+ *   <file>                import a website archive and open it
+ *   silex://...           deep link, see below
+ *
+ * Deep links (silex:// scheme, registered by the deep-link plugin) arrive
+ * as the only argument on Linux and Windows, and through `on_open_url` on
+ * macOS (`get_current` for the link that launched the app). All end up in
+ * `run`; links that create or download something ask first:
+ *   silex://open/<website_id>
+ *   silex://import?url=<archive URL>
+ *   silex://new?template=<template_id>[&name=<website name>]
+ */
+
+use std::path::{Path, PathBuf};
+
+use tauri::Manager;
+
+use crate::AppState;
+
+pub const DEEP_LINK_SCHEME: &str = "silex";
+
+#[derive(Debug)]
+pub enum LaunchAction {
+    Open(String),
+    Import(PathBuf),
+    ImportUrl(url::Url),
+    New { template: String, name: Option<String> },
+    /// A silex:// link, parsed when run so errors can be reported.
+    DeepLink(String),
+}
+
+/// Actions requested by command line arguments (`args[0]` is the executable).
+/// Relative file paths are resolved against `cwd`.
+pub fn parse_args(args: &[String], cwd: &Path) -> Vec<LaunchAction> {
+    let mut actions = Vec::new();
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        let website_id = if arg == "--open" {
+            args.next().map(String::as_str)
+        } else if let Some(id) = arg.strip_prefix("--open=") {
+            Some(id)
+        } else if arg.starts_with(&format!("{}:", DEEP_LINK_SCHEME)) {
+            actions.push(LaunchAction::DeepLink(arg.clone()));
+            continue;
+        } else if arg.starts_with('-') {
+            // Other flags (--stdio, ...)
+            continue;
+        } else {
+            let path = cwd.join(arg);
+            if path.is_file() {
+                actions.push(LaunchAction::Import(path));
+            } else {
+                tracing::warn!("Ignoring launch argument {:?}", arg);
+            }
+            continue;
+        };
+        match website_id {
+            Some(id) if !id.is_empty() && !id.starts_with('-') => {
+                actions.push(LaunchAction::Open(id.to_string()))
+            }
+            _ => tracing::warn!("--open expects a website id"),
+        }
+    }
+    actions
+}
+
+/// Parse a silex:// link into the action it requests.
+pub fn parse_deep_link(link: &str) -> Result<LaunchAction, String> {
+    let invalid = |reason: &str| format!("Invalid Silex link {}: {}", link, reason);
+    let url = url::Url::parse(link).map_err(|e| invalid(&e.to_string()))?;
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return Err(invalid("not a silex:// link"));
+    }
+    let param = |key: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+            .filter(|v| !v.is_empty())
+    };
+    match url.host_str() {
+        Some("open") => {
+            let id = url
+                .path_segments()
+                .and_then(|mut segments| segments.find(|s| !s.is_empty()))
+                .map(|id| percent_encoding::percent_decode_str(id).decode_utf8_lossy().into_owned())
+                .ok_or_else(|| invalid("missing website id"))?;
+            Ok(LaunchAction::Open(id))
+        }
+        Some("import") => {
+            let source = param("url").ok_or_else(|| invalid("missing url parameter"))?;
+            let source = url::Url::parse(&source).map_err(|e| invalid(&e.to_string()))?;
+            if !matches!(source.scheme(), "http" | "https") {
+                return Err(invalid("archives can only be imported from http(s) URLs"));
+            }
+            Ok(LaunchAction::ImportUrl(source))
+        }
+        Some("new") => Ok(LaunchAction::New {
+            template: param("template").unwrap_or_else(|| "blank".into()),
+            name: param("name"),
+        }),
+        _ => Err(invalid("expected silex://open, silex://import or silex://new")),
+    }
+}
+
+/// Bring the app to the front on the last used editor window.
+pub fn focus(app: &tauri::AppHandle) {
+    if let Some(window) = crate::focused_editor(app) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Called in the running instance when Silex is launched again.
+pub fn handle_second_instance(app: &tauri::AppHandle, args: Vec<String>, cwd: String) {
+    tracing::info!("Second instance launched with {:?}", args);
+    let actions = parse_args(&args, Path::new(&cwd));
+    // All windows may be closed while running in the tray
+    if actions.is_empty() && crate::editor_windows(app).is_empty() {
+        if let Err(e) = crate::open_editor_window(app, None) {
+            crate::menu::show_error(app, "Silex", e);
+        }
+    }
+    focus(app);
+    run(app, actions);
+}
+
+/// Perform launch actions, reporting failures in a dialog.
+pub fn run(app: &tauri::AppHandle, actions: Vec<LaunchAction>) {
+    for action in actions {
+        run_action(app, action);
+    }
+}
+
+fn run_action(app: &tauri::AppHandle, action: LaunchAction) {
+    match action {
+        LaunchAction::Open(website_id) => {
+            if let Err(e) = open_existing_website(app, &website_id) {
+                crate::menu::show_error(app, "Open Website", e);
+            }
+        }
+        LaunchAction::Import(path) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let result = crate::archive::import_website(&app, &crate::base_url(&app), &path).await;
+                if let Err(e) = result.and_then(|id| open_website(&app, &id)) {
+                    crate::menu::show_error(&app, "Import Website", e);
+                }
+            });
+        }
+        LaunchAction::ImportUrl(source) => confirm_import_url(app, source),
+        LaunchAction::New { template, name } => confirm_new(app, template, name),
+        LaunchAction::DeepLink(link) => match parse_deep_link(&link) {
+            // A link can't hold another link
+            Ok(LaunchAction::DeepLink(_)) => {}
+            Ok(action) => run_action(app, action),
+            Err(e) => crate::menu::show_error(app, "Open Link", e),
+        },
+    }
+}
+
+/// Like `open_website`, with a clear error for ids that aren't in storage
+/// (deleted site, or a link made on another computer).
+fn open_existing_website(app: &tauri::AppHandle, website_id: &str) -> Result<(), String> {
+    if !website_exists(app, website_id) {
+        return Err(format!(
+            "Website '{}' was not found. It may have been deleted, or the link was made on another computer.",
+            website_id
+        ));
+    }
+    open_website(app, website_id)
+}
+
+/// Whether `website_id` is a valid id of a website in storage.
+pub fn website_exists(app: &tauri::AppHandle, website_id: &str) -> bool {
+    crate::websites::validate_website_id(website_id).is_ok()
+        && crate::storage_path(app).join(website_id).is_dir()
+}
+
+/// Links can come from any web page: ask before acting on them. `then`
+/// runs only if the user accepts.
+fn confirm(
+    app: &tauri::AppHandle,
+    title: &str,
+    message: String,
+    ok_label: &str,
+    then: impl FnOnce(tauri::AppHandle) + Send + 'static,
+) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    let app_handle = app.clone();
+    app.dialog()
+        .message(message)
+        .title(title)
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            ok_label.into(),
+            "Cancel".into(),
+        ))
+        .show(move |accepted| {
+            if accepted {
+                then(app_handle);
+            }
+        });
+}
+
+/// Ask before downloading anything.
+fn confirm_import_url(app: &tauri::AppHandle, source: url::Url) {
+    let message = format!(
+        "Download and import a website from {}?\n\n{}",
+        source.host_str().unwrap_or("this address"),
+        source
+    );
+    confirm(app, "Import Website", message, "Import", move |app| {
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = import_url(&app, &source).await {
+                crate::menu::show_error(&app, "Import Website", e);
+            }
+        });
+    });
+}
+
+/// Ask before creating a website, so a page can't fill the storage with
+/// sites by opening links.
+fn confirm_new(app: &tauri::AppHandle, template: String, name: Option<String>) {
+    let message = match &name {
+        Some(name) => format!("Create a website named \"{}\" from the template '{}'?", name, template),
+        None => format!("Create a website from the template '{}'?", template),
+    };
+    confirm(app, "New Website", message, "Create", move |app| {
+        tauri::async_runtime::spawn(async move {
+            let result =
+                crate::templates::create_from_template(&app, &crate::base_url(&app), &template, name.as_deref())
+                    .await;
+            if let Err(e) = result.and_then(|id| open_website(&app, &id)) {
+                crate::menu::show_error(&app, "New Website", e);
+            }
+        });
+    });
+}
+
+/// Download an archive to a temporary file, import it and open the new site.
+async fn import_url(app: &tauri::AppHandle, source: &url::Url) -> Result<(), String> {
+    let resp = reqwest::get(source.clone())
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", source, e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Failed to download {}: {}", source, resp.status()));
+    }
+    let bytes = resp
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", source, e))?;
+    let path = std::env::temp_dir().join(format!("silex-import-{}.zip", rand::random::<u64>()));
+    std::fs::write(&path, &bytes).map_err(|e| format!("Failed to save download: {}", e))?;
+
+    let result = crate::archive::import_website(app, &crate::base_url(app), &path).await;
+    let _ = std::fs::remove_file(&path);
+    open_website(app, &result?)
+}
+
+/// Open a website in the editor: focus the window already editing it,
+/// else reuse the focused window if it shows the dashboard, else open a
+/// new window (never navigate away from another open project).
+pub fn open_website(app: &tauri::AppHandle, website_id: &str) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let is_open_in = |window: &tauri::WebviewWindow| {
+        state.project(window.label()).website_id.as_deref() == Some(website_id)
+    };
+    if let Some(window) = crate::editor_windows(app).into_iter().find(is_open_in) {
+        let _ = window.unminimize();
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    match crate::focused_editor(app) {
+        Some(window) if state.project(window.label()).website_id.is_none() => {
+            let url = format!(
+                "{}/?id={}",
+                crate::base_url(app),
+                url::form_urlencoded::byte_serialize(website_id.as_bytes()).collect::<String>()
+            );
+            crate::navigate_window(&window, &url)
+        }
+        _ => crate::open_editor_window(app, Some(website_id)).map(|_| ()),
+    }
+}
@@ -0,0 +1,216 @@
+/*
+ * Log files and the log viewer.
+ *
+ * Logs are written as JSON lines to <app data>/logs/silex.log, next to
+ * stdout (invisible in installed builds). The file is rotated past 5MB
+ * (silex.log.1 ... silex.log.4) and rotated files older than 14 days are
+ * deleted. Webview messages (`log_debug`) are logged with the "webview"
+ * target and always kept in the file.
+ *
+ * Help > Show Logs opens a viewer (logs.html) filtering by level and
+ * target, which can export a diagnostics bundle: logs, settings and
+ * versions in a zip, scrubbed like telemetry.
+ */
+
+use std::fs::File;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use serde_json::Value;
+use tauri::Manager;
+
+/// Target of webview messages.
+pub const WEBVIEW_TARGET: &str = "webview";
+
+const LOG_FILE: &str = "silex.log";
+const MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+/// Rotated files kept, besides the current one.
+const MAX_ROTATED_FILES: usize = 4;
+const MAX_AGE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+pub fn log_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("logs")
+}
+
+fn rotated_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("{}.{}", LOG_FILE, index))
+}
+
+struct LogFile {
+    dir: PathBuf,
+    file: Option<File>,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(&mut self) -> std::io::Result<&mut File> {
+        if self.file.is_none() {
+            std::fs::create_dir_all(&self.dir)?;
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.dir.join(LOG_FILE))?;
+            self.size = file.metadata().map(|m| m.len()).unwrap_or(0);
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().unwrap())
+    }
+
+    /// silex.log becomes silex.log.1, silex.log.1 becomes silex.log.2...
+    fn rotate(&mut self) {
+        self.file = None;
+        let _ = std::fs::remove_file(rotated_path(&self.dir, MAX_ROTATED_FILES));
+        for index in (1..MAX_ROTATED_FILES).rev() {
+            let _ = std::fs::rename(rotated_path(&self.dir, index), rotated_path(&self.dir, index + 1));
+        }
+        let _ = std::fs::rename(self.dir.join(LOG_FILE), rotated_path(&self.dir, 1));
+        remove_old_files(&self.dir);
+    }
+}
+
+fn remove_old_files(dir: &Path) {
+    for index in 1..=MAX_ROTATED_FILES {
+        let path = rotated_path(dir, index);
+        let expired = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > MAX_AGE);
+        if expired {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Writer for the `tracing_subscriber` file layer, shared by all threads.
+#[derive(Clone)]
+pub struct LogWriter(Arc<Mutex<LogFile>>);
+
+impl LogWriter {
+    pub fn new(data_dir: &Path) -> Self {
+        let dir = log_dir(data_dir);
+        remove_old_files(&dir);
+        Self(Arc::new(Mutex::new(LogFile { dir, file: None, size: 0 })))
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut log = self.0.lock().unwrap();
+        log.open()?;
+        if log.size > 0 && log.size + buf.len() as u64 > MAX_FILE_SIZE {
+            log.rotate();
+        }
+        let written = log.open()?.write(buf)?;
+        log.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.0.lock().unwrap().file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Log files, most recent first.
+fn log_files(dir: &Path) -> Vec<PathBuf> {
+    std::iter::once(dir.join(LOG_FILE))
+        .chain((1..=MAX_ROTATED_FILES).map(|index| rotated_path(dir, index)))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Log entries (JSON objects written by the file layer), most recent first.
+pub fn read_entries(data_dir: &Path, limit: usize) -> Vec<Value> {
+    let mut entries = Vec::new();
+    for path in log_files(&log_dir(data_dir)) {
+        let Ok(file) = File::open(&path) else { continue };
+        let mut lines: Vec<Value> = std::io::BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect();
+        lines.reverse();
+        entries.extend(lines);
+        if entries.len() >= limit {
+            break;
+        }
+    }
+    entries.truncate(limit);
+    entries
+}
+
+/// Open the log viewer, or focus it.
+pub fn show_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("logs") {
+        let _ = window.set_focus();
+        return;
+    }
+    let result = tauri::WebviewWindowBuilder::new(app, "logs", tauri::WebviewUrl::App("logs.html".into()))
+        .title("Logs")
+        .inner_size(900.0, 600.0)
+        .center()
+        .build();
+    if let Err(e) = result {
+        crate::menu::show_error(app, "Show Logs", e.to_string());
+    }
+}
+
+/// App and platform versions, for the diagnostics bundle.
+fn versions() -> Value {
+    serde_json::json!({
+        "silex": env!("CARGO_PKG_VERSION"),
+        "tauri": tauri::VERSION,
+        "webview": tauri::webview_version().ok(),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+    })
+}
+
+/// Ask where to save a diagnostics bundle, then write it.
+pub fn export_diagnostics(app: &tauri::AppHandle) {
+    use tauri_plugin_dialog::DialogExt;
+
+    let app_handle = app.clone();
+    app.dialog()
+        .file()
+        .set_title("Export Diagnostics")
+        .set_file_name("silex-diagnostics.zip")
+        .add_filter("Zip archive", &["zip"])
+        .save_file(move |path| {
+            let Some(path) = path.and_then(|p| p.into_path().ok()) else { return };
+            if let Err(e) = write_diagnostics(&app_handle, &path) {
+                crate::menu::show_error(&app_handle, "Export Diagnostics", e);
+            }
+        });
+}
+
+/// Write a zip with the logs, settings and versions, scrubbed of paths,
+/// home directories and website names.
+fn write_diagnostics(app: &tauri::AppHandle, path: &Path) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let mut add = |name: &str, content: &str| -> Result<(), String> {
+        zip.start_file(name, zip::write::SimpleFileOptions::default())
+            .and_then(|_| zip.write_all(content.as_bytes()).map_err(Into::into))
+            .map_err(|e| format!("Failed to write {}: {}", name, e))
+    };
+
+    let mut settings = serde_json::to_value(crate::settings::get(app)).unwrap_or_default();
+    crate::telemetry::scrub_value(&mut settings);
+    add("settings.json", &serde_json::to_string_pretty(&settings).unwrap_or_default())?;
+    add("versions.json", &serde_json::to_string_pretty(&versions()).unwrap_or_default())?;
+    for log in log_files(&log_dir(&data_dir)) {
+        let name = log.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let content = std::fs::read_to_string(&log).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        add(&format!("logs/{}", name), &crate::telemetry::scrub_str(&content))?;
+    }
+    zip.finish().map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    tracing::info!("Exported diagnostics to {}", path.display());
+    Ok(())
+}
@@ -10,86 +10,243 @@
 // Prevents an extra console window on Windows in release builds
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Mutex;
 
-use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
 use tokio::net::TcpListener;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
 use silex_server::Config;
 use tauri_plugin_updater::UpdaterExt;
 
+mod archive;
+mod close;
+mod console;
+mod dragdrop;
+mod launch;
+mod logs;
 mod mcp;
+mod menu;
+mod metrics;
+mod preview;
+mod recent;
+mod recovery;
+mod settings;
+mod shutdown;
+mod telemetry;
+mod templates;
+mod tray;
+mod websites;
+mod window_state;
 
 // ==================
-// Telemetry consent
+// App State
 // ==================
 
-fn telemetry_consent_path(data_dir: &PathBuf) -> PathBuf {
-    data_dir.join("telemetry_consent")
+/// Project open in one editor window.
+#[derive(Default, Clone)]
+struct WindowProject {
+    website_id: Option<String>,
+    website_name: Option<String>,
+    has_unsaved_changes: bool,
 }
 
-/// Returns Some(true) if opted in, Some(false) if opted out, None if never asked.
-fn read_telemetry_consent(data_dir: &PathBuf) -> Option<bool> {
-    std::fs::read_to_string(telemetry_consent_path(data_dir))
-        .ok()
-        .map(|s| s.trim() == "true")
+/// Project state of each editor window, keyed by window label.
+#[derive(Default)]
+struct AppState {
+    windows: Mutex<HashMap<String, WindowProject>>,
+    /// Editor window that last had focus: target of the menu, and of MCP
+    /// tools unless the session selected another window.
+    focused_window: Mutex<Option<String>>,
 }
 
-fn write_telemetry_consent(data_dir: &PathBuf, accepted: bool) {
-    let _ = std::fs::create_dir_all(data_dir);
-    let _ = std::fs::write(
-        telemetry_consent_path(data_dir),
-        if accepted { "true" } else { "false" },
-    );
+impl AppState {
+    fn project(&self, label: &str) -> WindowProject {
+        self.windows.lock().unwrap().get(label).cloned().unwrap_or_default()
+    }
+
+    fn update_project(&self, label: &str, update: impl FnOnce(&mut WindowProject)) -> WindowProject {
+        let mut windows = self.windows.lock().unwrap();
+        let project = windows.entry(label.to_string()).or_default();
+        update(project);
+        project.clone()
+    }
 }
 
-/// Prompt the user for telemetry consent (non-blocking, saves result for next launch).
-fn prompt_telemetry_consent(app: &tauri::AppHandle, data_dir: PathBuf) {
-    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+/// Address of the embedded silex-server, known once it is listening.
+struct ServerInfo {
+    port: u16,
+}
 
-    app.dialog()
-        .message(
-            "Help improve Silex by sending anonymous crash reports and basic usage data?\n\n\
-             No personal data or website content is ever collected.\n\
-             You can change this later in Settings.",
-        )
-        .title("Telemetry")
-        .kind(MessageDialogKind::Info)
-        .buttons(MessageDialogButtons::OkCancelCustom(
-            "Yes, help improve Silex".into(),
-            "No thanks".into(),
-        ))
-        .show(move |accepted| {
-            write_telemetry_consent(&data_dir, accepted);
-            if accepted {
-                tracing::info!("Telemetry opted in");
-            } else {
-                tracing::info!("Telemetry opted out");
-            }
-        });
+/// Base URL of the embedded silex-server (no trailing slash).
+fn base_url(app: &tauri::AppHandle) -> String {
+    let port = app.try_state::<ServerInfo>().map(|s| s.port).unwrap_or(6805);
+    format!("http://localhost:{}", port)
+}
+
+/// Website storage folder, as passed to silex-server through SILEX_DATA_PATH.
+fn storage_path(app: &tauri::AppHandle) -> PathBuf {
+    std::env::var_os("SILEX_DATA_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            app.path()
+                .app_data_dir()
+                .unwrap_or_else(|_| PathBuf::from("."))
+                .join("storage")
+        })
+}
+
+/// Time since the Unix epoch, for timestamps saved in files and events.
+fn unix_time() -> std::time::Duration {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
 }
 
 // ==================
-// App State
+// Editor windows
 // ==================
 
-struct AppState {
-    current_website_id: Mutex<Option<String>>,
-    current_website_name: Mutex<Option<String>>,
-    has_unsaved_changes: Mutex<bool>,
+/// Suffix of the next "editor-<n>" window label ("main" is the first window).
+static NEXT_EDITOR_WINDOW: AtomicU32 = AtomicU32::new(1);
+
+fn is_editor_window(label: &str) -> bool {
+    label == "main" || label.starts_with("editor-")
+}
+
+/// Open editor windows, in label order.
+fn editor_windows(app: &tauri::AppHandle) -> Vec<tauri::WebviewWindow> {
+    let mut windows: Vec<_> = app
+        .webview_windows()
+        .into_iter()
+        .filter(|(label, _)| is_editor_window(label))
+        .collect();
+    windows.sort_by(|(a, _), (b, _)| a.cmp(b));
+    windows.into_iter().map(|(_, w)| w).collect()
+}
+
+/// The editor window that last had focus, or any editor window.
+fn focused_editor(app: &tauri::AppHandle) -> Option<tauri::WebviewWindow> {
+    let focused = app.state::<AppState>().focused_window.lock().unwrap().clone();
+    focused
+        .and_then(|label| app.get_webview_window(&label))
+        .or_else(|| editor_windows(app).into_iter().next())
+}
+
+fn update_title(window: &tauri::WebviewWindow, project: &WindowProject) {
+    let title = match (&project.website_name, project.has_unsaved_changes) {
+        (Some(name), true) => format!("\u{2022} {} \u{2014} Silex", name),
+        (Some(name), false) => format!("{} \u{2014} Silex", name),
+        (None, _) => "Silex".to_string(),
+    };
+    let _ = window.set_title(&title);
+}
+
+/// Navigate a window to a URL (safe, no injection).
+fn navigate_window(window: &tauri::WebviewWindow, url: &str) -> Result<(), String> {
+    let url_json =
+        serde_json::to_string(url).map_err(|e| format!("Failed to encode URL: {}", e))?;
+    window
+        .eval(&format!("window.location.href = {}", url_json))
+        .map_err(|e| format!("Navigation failed: {}", e))
+}
+
+/// Navigate the focused editor window to a URL.
+fn navigate_to(app: &tauri::AppHandle, url: &str) -> Result<(), String> {
+    let window = focused_editor(app).ok_or_else(|| "No editor window".to_string())?;
+    navigate_window(&window, url)
 }
 
-impl Default for AppState {
-    fn default() -> Self {
-        Self {
-            current_website_id: Mutex::new(None),
-            current_website_name: Mutex::new(None),
-            has_unsaved_changes: Mutex::new(false),
+/// Editor window builder with the desktop bridge, shared by all editor windows.
+fn editor_window_builder<'a>(
+    app: &'a tauri::AppHandle,
+    label: &str,
+    url: &str,
+) -> Result<WebviewWindowBuilder<'a, tauri::Wry, tauri::AppHandle>, String> {
+    let url = url.parse().map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    Ok(WebviewWindowBuilder::new(app, label, WebviewUrl::External(url))
+        .title("Silex")
+        .initialization_script(include_str!("../scripts/desktop-bridge.js")))
+}
+
+/// Track focus and geometry, keep project state in sync, import dropped
+/// files and ask before closing a window with unsaved changes.
+fn watch_editor_window(window: &tauri::WebviewWindow) {
+    let app = window.app_handle().clone();
+    let label = window.label().to_string();
+    window.on_window_event(move |event| match event {
+        tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => {
+            if let Some(window) = app.get_webview_window(&label) {
+                window_state::capture(&window);
+            }
         }
-    }
+        tauri::WindowEvent::DragDrop(event) => {
+            if let Some(window) = app.get_webview_window(&label) {
+                dragdrop::handle(&window, event);
+            }
+        }
+        tauri::WindowEvent::Focused(true) => {
+            *app.state::<AppState>().focused_window.lock().unwrap() = Some(label.clone());
+            menu::refresh(&app);
+        }
+        tauri::WindowEvent::CloseRequested { api, .. } => {
+            let project = app.state::<AppState>().project(&label);
+            if project.has_unsaved_changes {
+                api.prevent_close();
+                if let Some(window) = app.get_webview_window(&label) {
+                    close::confirm_close(&window, project.website_name.as_deref(), close::Closing::Window);
+                }
+            }
+        }
+        tauri::WindowEvent::Destroyed => {
+            let state = app.state::<AppState>();
+            state.windows.lock().unwrap().remove(&label);
+            let mut focused = state.focused_window.lock().unwrap();
+            if focused.as_deref() == Some(label.as_str()) {
+                *focused = None;
+            }
+            drop(focused);
+            app.state::<console::ConsoleLog>().forget_window(&label);
+            let mcp_handle = app.clone();
+            let closed = label.clone();
+            tauri::async_runtime::spawn(async move { mcp::forget_window(&mcp_handle, &closed).await });
+            menu::refresh(&app);
+            window_state::save(&app);
+        }
+        _ => {}
+    });
+}
+
+/// Open a new editor window on the dashboard, or on a website.
+fn open_editor_window(app: &tauri::AppHandle, website_id: Option<&str>) -> Result<tauri::WebviewWindow, String> {
+    let label = loop {
+        let label = format!("editor-{}", NEXT_EDITOR_WINDOW.fetch_add(1, Ordering::Relaxed));
+        if app.get_webview_window(&label).is_none() {
+            break label;
+        }
+    };
+    let url = match website_id {
+        Some(id) => format!(
+            "{}/?id={}",
+            base_url(app),
+            url::form_urlencoded::byte_serialize(id.as_bytes()).collect::<String>()
+        ),
+        None => format!("{}/", base_url(app)),
+    };
+    let window = editor_window_builder(app, &label, &url)?
+        .inner_size(1280.0, 800.0)
+        .visible(false)
+        .build()
+        .map_err(|e| format!("Failed to open window: {}", e))?;
+    window_state::restore(&window);
+    let _ = window.show();
+    watch_editor_window(&window);
+    *app.state::<AppState>().focused_window.lock().unwrap() = Some(label);
+    Ok(window)
 }
 
 // ==================
@@ -98,40 +255,103 @@ impl Default for AppState {
 
 #[tauri::command]
 fn set_current_project(
-    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
     state: tauri::State<'_, AppState>,
     website_id: String,
     website_name: String,
 ) {
-    *state.current_website_id.lock().unwrap() = Some(website_id);
-    *state.current_website_name.lock().unwrap() = Some(website_name.clone());
-    *state.has_unsaved_changes.lock().unwrap() = false;
+    let app = window.app_handle();
+    telemetry::add_sensitive_term(&website_name);
+    recent::record(app, &website_id, &website_name);
+    let project = state.update_project(window.label(), |p| {
+        p.website_id = Some(website_id);
+        p.website_name = Some(website_name);
+        p.has_unsaved_changes = false;
+    });
+    update_title(&window, &project);
+    menu::refresh(app);
+    menu::refresh_recent(app);
+}
 
-    if let Some(window) = app.get_webview_window("main") {
-        let _ = window.set_title(&format!("{} \u{2014} Silex", website_name));
-    }
+#[tauri::command]
+fn clear_current_project(window: tauri::WebviewWindow, state: tauri::State<'_, AppState>) {
+    let project = state.update_project(window.label(), |p| *p = WindowProject::default());
+    update_title(&window, &project);
+    menu::refresh(window.app_handle());
 }
 
 #[tauri::command]
-fn clear_current_project(app: tauri::AppHandle, state: tauri::State<'_, AppState>) {
-    *state.current_website_id.lock().unwrap() = None;
-    *state.current_website_name.lock().unwrap() = None;
-    *state.has_unsaved_changes.lock().unwrap() = false;
+fn mark_unsaved(window: tauri::WebviewWindow, state: tauri::State<'_, AppState>) {
+    let project = state.update_project(window.label(), |p| p.has_unsaved_changes = true);
+    update_title(&window, &project);
+}
 
-    if let Some(window) = app.get_webview_window("main") {
-        let _ = window.set_title("Silex");
+/// Result of an editor save, reported by the bridge: for every save
+/// (storage:end:store / storage:error:store), with `clean` when no change
+/// was made while it ran, and for saves requested with a token (menu-save
+/// from the close flow), which settle the matching waiter.
+#[tauri::command]
+fn report_save_result(
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, AppState>,
+    waiters: tauri::State<'_, close::SaveWaiters>,
+    token: Option<u64>,
+    success: bool,
+    clean: Option<bool>,
+    error: Option<String>,
+) {
+    let result = if success {
+        // Changes made during the save are not in it: keep them marked
+        if clean == Some(true) {
+            let project = state.update_project(window.label(), |p| p.has_unsaved_changes = false);
+            update_title(&window, &project);
+            if let Some(website_id) = &project.website_id {
+                recovery::clear(window.app_handle(), website_id);
+            }
+        }
+        Ok(())
+    } else {
+        let error = error.unwrap_or_else(|| "Unknown error".into());
+        if token.is_none() {
+            tracing::warn!("Save failed in {}: {}", window.label(), error);
+        }
+        Err(error)
+    };
+    if let Some(token) = token {
+        waiters.resolve(window.label(), token, result);
     }
 }
 
+/// Crash recovery: unsaved project data of the calling window, pushed
+/// periodically by the bridge while the project is dirty.
 #[tauri::command]
-fn mark_unsaved(app: tauri::AppHandle, state: tauri::State<'_, AppState>) {
-    *state.has_unsaved_changes.lock().unwrap() = true;
+fn write_recovery(
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, AppState>,
+    data: serde_json::Value,
+) -> Result<(), String> {
+    let project = state.project(window.label());
+    let website_id = project.website_id.ok_or("No project open")?;
+    let name = project.website_name.unwrap_or_else(|| website_id.clone());
+    recovery::write(window.app_handle(), &website_id, &name, data)
+}
 
-    if let Some(name) = state.current_website_name.lock().unwrap().as_ref() {
-        if let Some(window) = app.get_webview_window("main") {
-            let _ = window.set_title(&format!("\u{2022} {} \u{2014} Silex", name));
-        }
-    }
+/// Open another editor window (dashboard, or the given website). Returns its label.
+#[tauri::command]
+fn new_editor_window(app: tauri::AppHandle, website_id: Option<String>) -> Result<String, String> {
+    open_editor_window(&app, website_id.as_deref()).map(|w| w.label().to_string())
+}
+
+/// Recently opened projects, most recent first (dashboard "continue where you left off").
+#[tauri::command]
+fn get_recent_projects(app: tauri::AppHandle) -> Vec<recent::RecentProject> {
+    recent::list(&app)
+}
+
+#[tauri::command]
+fn clear_recent_projects(app: tauri::AppHandle) {
+    recent::clear(&app);
+    menu::refresh_recent(&app);
 }
 
 #[tauri::command]
@@ -143,47 +363,320 @@ fn open_folder(path: String) {
 
 #[tauri::command]
 fn log_debug(message: String) {
-    tracing::debug!("[webview] {message}");
+    tracing::debug!(target: logs::WEBVIEW_TARGET, "[webview] {message}");
 }
 
+/// Console output and uncaught errors forwarded by the bridge.
 #[tauri::command]
-fn get_glitchtip_dsn() -> Option<String> {
-    // Only expose DSN to the frontend if the user has opted in
-    let data_dir = dirs::data_dir()?.join("org.silex.desktop");
-    if read_telemetry_consent(&data_dir) == Some(true) {
-        option_env!("GLITCHTIP_DSN").map(String::from)
+fn log_console(app: tauri::AppHandle, window: tauri::WebviewWindow, entries: Vec<console::ConsoleEntry>) {
+    console::record(&app, window.label(), entries);
+}
+
+/// Log file entries for the log viewer, most recent first.
+#[tauri::command]
+fn get_logs(app: tauri::AppHandle, limit: Option<usize>) -> Result<Vec<serde_json::Value>, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(logs::read_entries(&data_dir, limit.unwrap_or(5000)))
+}
+
+#[tauri::command]
+fn open_log_folder(app: tauri::AppHandle) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    open::that(logs::log_dir(&data_dir)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn export_diagnostics(app: tauri::AppHandle) {
+    logs::export_diagnostics(&app);
+}
+
+#[tauri::command]
+fn get_glitchtip_dsn(app: tauri::AppHandle) -> Option<String> {
+    // Only expose DSN to the frontend if the user opted in to something it
+    // reports, or to record what it would report
+    let settings = settings::get(&app);
+    let consent = settings.telemetry.unwrap_or_default();
+    let recording = settings.telemetry_recording;
+    if consent.crash_reports || consent.performance || recording == telemetry::TelemetryRecording::RecordOnly {
+        telemetry::dsn(&settings)
     } else {
         None
     }
 }
 
-fn show_quit_dialog(app: &tauri::AppHandle) {
+/// Sentry browser bundle, release and environment for the webview client.
+#[tauri::command]
+fn get_sentry_browser_client(app: tauri::AppHandle) -> Option<telemetry::BrowserClient> {
+    telemetry::browser_client(&settings::get(&app))
+}
+
+/// Scrub, record and filter an event of the webview Sentry client (its
+/// beforeSend hooks). Returns the event to send, if any.
+#[tauri::command]
+fn filter_webview_telemetry(app: tauri::AppHandle, kind: String, event: serde_json::Value) -> Option<serde_json::Value> {
+    telemetry::filter_webview_event(&app, &kind, event)
+}
+
+/// Recorded telemetry (inspector), most recent first.
+#[tauri::command]
+fn list_recorded_telemetry(app: tauri::AppHandle, limit: Option<usize>) -> Result<Vec<serde_json::Value>, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(telemetry::list_recorded(&data_dir, limit.unwrap_or(500)))
+}
+
+#[tauri::command]
+fn clear_recorded_telemetry(app: tauri::AppHandle) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    telemetry::clear_recorded(&data_dir);
+    Ok(())
+}
+
+#[tauri::command]
+fn show_telemetry_inspector(app: tauri::AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("telemetry") {
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    WebviewWindowBuilder::new(&app, "telemetry", WebviewUrl::App("telemetry.html".into()))
+        .title("Recorded Telemetry")
+        .inner_size(760.0, 560.0)
+        .build()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_telemetry_consent(app: tauri::AppHandle) -> telemetry::TelemetryConsent {
+    telemetry::consent(&app)
+}
+
+#[tauri::command]
+fn set_telemetry_consent(app: tauri::AppHandle, consent: telemetry::TelemetryConsent) -> Result<(), String> {
+    telemetry::set_consent(&app, consent)
+}
+
+/// Settings for the settings window, with the values set by environment variables.
+#[tauri::command]
+fn get_settings(store: tauri::State<'_, settings::SettingsStore>) -> settings::SettingsView {
+    settings::SettingsView {
+        settings: store.stored(),
+        overrides: store.overrides().clone(),
+    }
+}
+
+/// Save settings from the settings window. Returns the names of the
+/// settings that take effect at next launch.
+#[tauri::command]
+fn set_settings(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, settings::SettingsStore>,
+    settings: settings::Settings,
+) -> Result<Vec<&'static str>, String> {
+    settings::validate(&settings)?;
+    let before = store.effective();
+    store.update(|s| *s = settings)?;
+    Ok(settings::apply(&app, &before, &store.effective()))
+}
+
+#[tauri::command]
+fn list_templates(app: tauri::AppHandle) -> Vec<templates::TemplateMeta> {
+    templates::list_templates(&app)
+}
+
+/// Save a website as a user template. `preview` is an optional PNG data URL
+/// (typically a canvas screenshot taken by the editor).
+#[tauri::command]
+fn save_as_template(
+    app: tauri::AppHandle,
+    website_id: String,
+    name: String,
+    description: Option<String>,
+    preview: Option<String>,
+) -> Result<templates::TemplateMeta, String> {
+    use base64::Engine;
+
+    let png = preview
+        .as_deref()
+        .and_then(|url| url.strip_prefix("data:image/png;base64,"))
+        .map(|data| base64::engine::general_purpose::STANDARD.decode(data))
+        .transpose()
+        .map_err(|e| format!("Invalid preview image: {}", e))?;
+    templates::save_as_template(
+        &app,
+        &website_id,
+        &name,
+        description.as_deref().unwrap_or(""),
+        png.as_deref(),
+    )
+}
+
+/// Create a website from a template and return its id.
+#[tauri::command]
+async fn create_from_template(
+    app: tauri::AppHandle,
+    template_id: String,
+    name: Option<String>,
+) -> Result<String, String> {
+    templates::create_from_template(&app, &base_url(&app), &template_id, name.as_deref()).await
+}
+
+/// Serve a website's published output on localhost and return the URL.
+/// Defaults to the current project.
+#[tauri::command]
+async fn start_preview(
+    app: tauri::AppHandle,
+    website_id: Option<String>,
+    live_reload: Option<bool>,
+) -> Result<String, String> {
+    let website_id = match website_id {
+        Some(id) => id,
+        None => current_website_id(&app).ok_or("No project open")?,
+    };
+    preview::start_preview(&app, &website_id, live_reload.unwrap_or(true)).await
+}
+
+#[tauri::command]
+fn stop_preview(app: tauri::AppHandle, website_id: String) -> bool {
+    preview::stop_preview(&app, &website_id)
+}
+
+/// Called by the bridge after a successful publication (live reload).
+#[tauri::command]
+fn notify_published(app: tauri::AppHandle, website_id: String) {
+    preview::notify_published(&app, &website_id);
+}
+
+/// Share a website's preview on the LAN and show its QR code window, once
+/// the user agreed. Defaults to the current project and a 15 minute
+/// timeout. Returns None if the user declined.
+#[tauri::command]
+async fn start_lan_preview(
+    app: tauri::AppHandle,
+    website_id: Option<String>,
+    minutes: Option<u64>,
+) -> Result<Option<preview::LanPreview>, String> {
+    let website_id = match website_id {
+        Some(id) => id,
+        None => current_website_id(&app).ok_or("No project open")?,
+    };
+    share_lan_preview(&app, &website_id, minutes.unwrap_or(15)).await
+}
+
+#[tauri::command]
+fn stop_lan_preview(app: tauri::AppHandle) -> bool {
+    preview::stop_lan_preview(&app)
+}
+
+/// Current LAN share, read by the QR code window.
+#[tauri::command]
+fn get_lan_preview(app: tauri::AppHandle) -> Option<preview::LanPreview> {
+    preview::lan_preview(&app)
+}
+
+fn show_lan_preview_window(app: &tauri::AppHandle, info: &preview::LanPreview) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window("lan-preview") {
+        let _ = window.eval("window.location.reload()");
+        return window.set_focus();
+    }
+    WebviewWindowBuilder::new(app, "lan-preview", WebviewUrl::App("lan-preview.html".into()))
+        .title("Preview on your phone")
+        .inner_size(360.0, 480.0)
+        .resizable(false)
+        .always_on_top(true)
+        .build()?;
+    tracing::debug!("LAN preview window opened for {}", info.url);
+    Ok(())
+}
+
+/// Ask before exposing the preview on the network (whoever asks for it:
+/// menu, webview), then share it and show the QR code window.
+async fn share_lan_preview(
+    app: &tauri::AppHandle,
+    website_id: &str,
+    minutes: u64,
+) -> Result<Option<preview::LanPreview>, String> {
     use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 
-    let app_handle = app.clone();
+    let minutes = minutes.clamp(preview::LAN_MIN_MINUTES, preview::LAN_MAX_MINUTES);
+    let (tx, rx) = tokio::sync::oneshot::channel();
     app.dialog()
-        .message("Do you want to save changes before quitting?")
-        .title("Silex")
-        .kind(MessageDialogKind::Warning)
+        .message(format!(
+            "Share the published site on your local network for {} minute{}?\n\n\
+             Anyone on the same network with the link or QR code can view it. \
+             The editor and the MCP server stay private.",
+            minutes,
+            if minutes == 1 { "" } else { "s" }
+        ))
+        .title("Preview on LAN")
+        .kind(MessageDialogKind::Info)
         .buttons(MessageDialogButtons::OkCancelCustom(
-            "Save & Quit".into(),
-            "Quit".into(),
+            "Share".into(),
+            "Cancel".into(),
         ))
-        .show(move |result| {
-            if result {
-                let _ = app_handle.emit("menu-save", ());
-                // Give a moment for save, then close
-                let handle = app_handle.clone();
-                std::thread::spawn(move || {
-                    std::thread::sleep(std::time::Duration::from_secs(2));
-                    if let Some(window) = handle.get_webview_window("main") {
-                        let _ = window.destroy();
-                    }
-                });
-            } else if let Some(window) = app_handle.get_webview_window("main") {
-                let _ = window.destroy();
-            }
+        .show(move |accepted| {
+            let _ = tx.send(accepted);
         });
+    if !rx.await.unwrap_or(false) {
+        return Ok(None);
+    }
+    let info = preview::start_lan_preview(app, website_id, minutes).await?;
+    show_lan_preview_window(app, &info).map_err(|e| e.to_string())?;
+    Ok(Some(info))
+}
+
+/// Preview on LAN menu item: share the current project's preview.
+fn confirm_lan_preview(app: &tauri::AppHandle) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+    let Some(website_id) = current_website_id(app) else {
+        app.dialog()
+            .message("Open a project to share its preview.")
+            .title("Preview on LAN")
+            .kind(MessageDialogKind::Warning)
+            .show(|_| {});
+        return;
+    };
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = share_lan_preview(&app_handle, &website_id, 15).await {
+            app_handle
+                .dialog()
+                .message(e)
+                .title("Preview on LAN")
+                .kind(MessageDialogKind::Error)
+                .show(|_| {});
+        }
+    });
+}
+
+/// Project open in the focused editor window.
+fn current_website_id(app: &tauri::AppHandle) -> Option<String> {
+    let window = focused_editor(app)?;
+    app.state::<AppState>().project(window.label()).website_id
+}
+
+/// Preview the current project's published output in the default browser.
+fn open_preview(app: &tauri::AppHandle) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match current_website_id(&app) {
+            Some(id) => preview::start_preview(&app, &id, true).await,
+            None => Err("Open a project to preview its published output.".into()),
+        };
+        match result {
+            Ok(url) => {
+                let _ = open::that(url);
+            }
+            Err(e) => {
+                app.dialog()
+                    .message(e)
+                    .title("Preview")
+                    .kind(MessageDialogKind::Warning)
+                    .show(|_| {});
+            }
+        }
+    });
 }
 
 // ==================
@@ -193,9 +686,34 @@ fn show_quit_dialog(app: &tauri::AppHandle) {
 fn check_for_updates(app: tauri::AppHandle) {
     use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 
+    let settings = settings::get(&app);
+    if !settings.check_for_updates {
+        tracing::info!("Update checks disabled in settings");
+        return;
+    }
+    let updater = match &settings.update_endpoint {
+        Some(endpoint) => endpoint
+            .parse()
+            .map_err(|e: url::ParseError| e.to_string())
+            .and_then(|url| {
+                app.updater_builder()
+                    .endpoints(vec![url])
+                    .and_then(|b| b.build())
+                    .map_err(|e| e.to_string())
+            }),
+        None => app.updater().map_err(|e| e.to_string()),
+    };
+    let updater = match updater {
+        Ok(updater) => updater,
+        Err(e) => {
+            tracing::warn!("Updater not available: {}", e);
+            return;
+        }
+    };
+
     tauri::async_runtime::spawn(async move {
         tracing::info!("Checking for updates...");
-        match app.updater().expect("updater not configured").check().await {
+        match updater.check().await {
             Ok(Some(update)) => {
                 let version = update.version.clone();
                 let app_clone = app.clone();
@@ -218,6 +736,7 @@ fn check_for_updates(app: tauri::AppHandle) {
                                 match update.download_and_install(|_, _| {}, || {}).await {
                                     Ok(_) => {
                                         tracing::info!("Update installed, restarting...");
+                                        shutdown::shutdown(&app_clone).await;
                                         app_clone.restart();
                                     }
                                     Err(e) => {
@@ -242,22 +761,44 @@ fn check_for_updates(app: tauri::AppHandle) {
 // Server
 // ==================
 
-async fn start_server(pending_evals: mcp::PendingEvals, data_path: std::path::PathBuf) -> u16 {
-    // Set SILEX_DATA_PATH so Config::from_env() picks it up (unless already set by user)
-    if std::env::var("SILEX_DATA_PATH").is_err() {
-        std::env::set_var("SILEX_DATA_PATH", &data_path);
-    }
+/// Minimum time between two dialogs about panicking requests.
+const PANIC_NOTICE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+/// Restarts allowed within RESTART_WINDOW before giving up.
+const MAX_SERVER_RESTARTS: usize = 5;
+const RESTART_WINDOW: std::time::Duration = std::time::Duration::from_secs(60);
+
+async fn start_server(
+    app_handle: tauri::AppHandle,
+    pending_evals: mcp::PendingEvals,
+    publish_progress: mcp::PublishProgress,
+    data_path: std::path::PathBuf,
+) -> u16 {
+    // Set SILEX_DATA_PATH so Config::from_env() picks it up (the settings
+    // folder, or the one the user set in the environment)
+    std::env::set_var("SILEX_DATA_PATH", &data_path);
     let config = Config::from_env();
 
     let (app, port) = silex_server::build_app(config).await;
 
-    // Add eval-callback route for JS→Rust result passing (same origin, no CORS)
+    // Add eval-callback and publish-progress routes for JS→Rust result passing (same origin, no CORS),
+    // and health/metrics routes
     let app = app
         .route(
             "/eval-callback/{id}",
             axum::routing::post(mcp::eval_callback),
         )
-        .layer(axum::Extension(pending_evals));
+        .route(
+            "/publish-progress/{id}",
+            axum::routing::post(mcp::publish_progress),
+        )
+        .route("/health", axum::routing::get(metrics::health))
+        .route("/metrics", axum::routing::get(metrics::render))
+        // Inside track_request, so that panics count as 500 responses
+        .layer(tower_http::catch_panic::CatchPanicLayer::custom(handler_panicked(app_handle.clone())))
+        .layer(axum::middleware::from_fn(metrics::track_request))
+        .layer(axum::Extension(pending_evals))
+        .layer(axum::Extension(publish_progress))
+        .layer(axum::Extension(app_handle.clone()));
 
     let addr = SocketAddr::from(([127, 0, 0, 1], port));
     let listener = match TcpListener::bind(addr).await {
@@ -272,13 +813,125 @@ async fn start_server(pending_evals: mcp::PendingEvals, data_path: std::path::Pa
     let port = addr.port();
     tracing::info!("Silex server listening on http://{}", addr);
 
-    tokio::spawn(async move {
-        axum::serve(listener, app).await.unwrap();
-    });
+    let shutdown = app_handle.state::<shutdown::Shutdown>();
+    shutdown.track(tauri::async_runtime::spawn(supervise_server(
+        app_handle.clone(),
+        listener,
+        app,
+        shutdown.token().clone(),
+    )));
 
     port
 }
 
+/// Answer a request whose handler panicked with a 500, count it in /metrics
+/// and tell the user, at most once per PANIC_NOTICE_INTERVAL since a broken
+/// route may panic on every request.
+fn handler_panicked(
+    app_handle: tauri::AppHandle,
+) -> impl Fn(Box<dyn std::any::Any + Send + 'static>) -> axum::response::Response + Clone {
+    use axum::response::IntoResponse;
+
+    let last_notice = std::sync::Arc::new(Mutex::new(None::<std::time::Instant>));
+    move |panic| {
+        let message = panic
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| panic.downcast_ref::<&str>().copied())
+            .unwrap_or("unknown error")
+            .to_string();
+        tracing::error!("Silex server request panicked: {}", message);
+        metrics::record_handler_panic();
+
+        let mut last = last_notice.lock().unwrap();
+        if !last.is_some_and(|t| t.elapsed() < PANIC_NOTICE_INTERVAL) {
+            *last = Some(std::time::Instant::now());
+            menu::show_error(
+                &app_handle,
+                "Silex Server",
+                format!(
+                    "A request to the Silex server failed unexpectedly.\n\n{}\n\nIf the editor shows errors, save your work and reopen the website.",
+                    message
+                ),
+            );
+        }
+        (axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("Internal error: {}", message)).into_response()
+    }
+}
+
+/// Serve the app, restarting the server task on the same port if it fails
+/// (accept error or panic in the task itself), and tell the user. Gives up
+/// after too many restarts. Panics in request handlers never reach it: each
+/// connection runs in its own task, so those are caught per request by
+/// `handler_panicked`.
+/// Stops, finishing in-flight requests, when `shutdown` is cancelled.
+async fn supervise_server(
+    app_handle: tauri::AppHandle,
+    listener: TcpListener,
+    app: axum::Router,
+    shutdown: tokio_util::sync::CancellationToken,
+) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+    let addr = listener.local_addr().unwrap();
+    let mut listener = Some(listener);
+    let mut restarts: Vec<std::time::Instant> = Vec::new();
+    loop {
+        let listener = match listener.take() {
+            Some(listener) => listener,
+            None => match TcpListener::bind(addr).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    tracing::warn!("Failed to bind {} again: {}", addr, e);
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    restarts.push(std::time::Instant::now());
+                    restarts.retain(|t| t.elapsed() < RESTART_WINDOW);
+                    if restarts.len() > MAX_SERVER_RESTARTS {
+                        break;
+                    }
+                    continue;
+                }
+            },
+        };
+        let router = app.clone();
+        let stop = shutdown.clone();
+        let serve = async move { axum::serve(listener, router).with_graceful_shutdown(stop.cancelled_owned()).await };
+        let error = match tokio::spawn(serve).await {
+            // Stopped on purpose
+            Ok(Ok(())) => return,
+            Ok(Err(e)) => e.to_string(),
+            Err(e) => e.to_string(),
+        };
+        tracing::error!("Silex server stopped: {}", error);
+        if shutdown.is_cancelled() {
+            return;
+        }
+
+        restarts.push(std::time::Instant::now());
+        restarts.retain(|t| t.elapsed() < RESTART_WINDOW);
+        if restarts.len() > MAX_SERVER_RESTARTS {
+            break;
+        }
+        metrics::record_server_restart();
+        tracing::warn!("Restarting the Silex server on {}", addr);
+        app_handle
+            .dialog()
+            .message(format!(
+                "The Silex server stopped unexpectedly and was restarted.\n\n{}\n\nIf the editor shows errors, close and reopen the website.",
+                error
+            ))
+            .title("Silex Server Restarted")
+            .kind(MessageDialogKind::Warning)
+            .show(|_| {});
+    }
+    tracing::error!("Silex server keeps failing, not restarting it anymore");
+    menu::show_error(
+        &app_handle,
+        "Silex Server",
+        "The Silex server keeps stopping unexpectedly. Save your work if you can, then restart Silex.".into(),
+    );
+}
+
 // ==================
 // Main
 // ==================
@@ -302,60 +955,79 @@ fn main() {
         .unwrap_or_else(|| PathBuf::from("."))
         .join("org.silex.desktop");
     let _ = std::fs::create_dir_all(&app_data_dir);
+    let settings_store = settings::load(&app_data_dir);
+    let startup_settings = settings_store.effective();
 
     // Initialize error tracking (GlitchTip / Sentry-compatible).
-    // Sentry is always initialized when GLITCHTIP_DSN is set, but events are only
-    // sent if the user has opted in. This way consent takes effect immediately
-    // (no need to restart after opting in on first launch).
-    let consent_dir_for_send = app_data_dir.clone();
-    let consent_dir_for_traces = app_data_dir.clone();
+    // Sentry is always initialized when a DSN is configured (settings,
+    // SILEX_GLITCHTIP_DSN or built in with GLITCHTIP_DSN), but each kind of
+    // data is only sent if the user consented to its category. The hooks read
+    // the consent kept in memory by the settings store, so changes take effect
+    // immediately (no restart needed) without reading the disk for each event.
+    // Events are scrubbed first, and recorded locally if the inspector is on.
     let _sentry_guard = sentry::init(sentry::ClientOptions {
-        dsn: option_env!("GLITCHTIP_DSN")
-            .and_then(|s| s.parse().ok()),
+        dsn: telemetry::dsn(&startup_settings).and_then(|s| s.parse().ok()),
         release: Some(env!("CARGO_PKG_VERSION").into()),
-        environment: Some(
-            if cfg!(debug_assertions) { "development" } else { "production" }.into(),
-        ),
-        before_send: Some(std::sync::Arc::new(move |event| {
-            if read_telemetry_consent(&consent_dir_for_send) == Some(true) {
-                Some(event)
-            } else {
-                None
-            }
-        })),
-        // Sample 100% of transactions (volume is low for a desktop app),
-        // but only if the user has opted in.
-        traces_sampler: Some(std::sync::Arc::new(move |_ctx| {
-            if read_telemetry_consent(&consent_dir_for_traces) == Some(true) {
-                1.0
-            } else {
-                0.0
-            }
-        })),
-        // Track sessions for user count and crash-free rate
-        auto_session_tracking: true,
+        environment: Some(telemetry::environment(&startup_settings).into()),
+        transport: Some(telemetry::transport_factory(app_data_dir.clone())),
+        before_send: Some(std::sync::Arc::new(telemetry::before_send)),
+        before_breadcrumb: Some(std::sync::Arc::new(telemetry::scrub_breadcrumb)),
+        traces_sampler: Some(std::sync::Arc::new(telemetry::sample_trace)),
+        // Sessions (user count and crash-free rate) are started below,
+        // only with consent to the sessions category
+        auto_session_tracking: false,
         session_mode: sentry::SessionMode::Application,
         ..Default::default()
     });
+    telemetry::apply_sessions(&startup_settings.telemetry.unwrap_or_default());
     sentry::configure_scope(|scope| {
         scope.set_tag("os", std::env::consts::OS);
         scope.set_tag("arch", std::env::consts::ARCH);
     });
 
+    // Settings log filter, RUST_LOG overriding it
+    let log_filter = || {
+        tracing_subscriber::EnvFilter::try_new(&startup_settings.log_filter)
+            .unwrap_or_else(|_| settings::DEFAULT_LOG_FILTER.into())
+    };
+    // The log file also keeps webview messages, for bug reports
+    let file_filter = log_filter().add_directive(
+        format!("{}=debug", logs::WEBVIEW_TARGET).parse().expect("valid directive"),
+    );
+    let log_writer = logs::LogWriter::new(&app_data_dir);
     tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(log_filter()))
         .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "silex_server=info,silex_desktop=info".into()),
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(move || log_writer.clone())
+                .with_filter(file_filter),
         )
-        .with(tracing_subscriber::fmt::layer())
-        .with(sentry::integrations::tracing::layer())
+        .with(sentry::integrations::tracing::layer().with_filter(log_filter()))
         .init();
+    if let Some(Err(e)) = startup_settings.glitchtip_dsn.as_deref().map(telemetry::validate_dsn) {
+        tracing::warn!("{}, using the default one", e);
+    }
+
+    let mut builder = tauri::Builder::default();
+    // Single instance: a second launch forwards its arguments and exits.
+    // Must be the first plugin. Not for --stdio, where the agent owns the
+    // process and talks MCP over its stdin/stdout.
+    if !std::env::args().any(|a| a == "--stdio") {
+        builder = builder.plugin(tauri_plugin_single_instance::init(launch::handle_second_instance));
+    }
 
-    tauri::Builder::default()
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .manage(settings_store)
         .manage(AppState::default())
+        .manage(close::SaveWaiters::default())
+        .manage(preview::PreviewServers::default())
+        .manage(console::ConsoleLog::default())
         .invoke_handler(tauri::generate_handler![
             set_current_project,
             clear_current_project,
@@ -363,12 +1035,41 @@ fn main() {
             open_folder,
             log_debug,
             get_glitchtip_dsn,
+            get_sentry_browser_client,
+            get_telemetry_consent,
+            set_telemetry_consent,
+            filter_webview_telemetry,
+            list_recorded_telemetry,
+            clear_recorded_telemetry,
+            show_telemetry_inspector,
+            log_console,
+            get_logs,
+            open_log_folder,
+            export_diagnostics,
+            get_settings,
+            set_settings,
+            list_templates,
+            save_as_template,
+            create_from_template,
+            start_preview,
+            stop_preview,
+            notify_published,
+            start_lan_preview,
+            stop_lan_preview,
+            get_lan_preview,
+            get_recent_projects,
+            clear_recent_projects,
+            new_editor_window,
+            report_save_result,
+            write_recovery,
         ])
         .setup(|app| {
-            // Log app launch with OS/arch info (visible in Issues even without errors)
+            // Log app launch with OS/arch info (visible in Issues even without errors),
+            // counted as usage data
             sentry::capture_event(sentry::protocol::Event {
                 message: Some("app_started".into()),
                 level: sentry::Level::Info,
+                tags: [("category".to_string(), telemetry::USAGE_TAG.to_string())].into(),
                 ..Default::default()
             });
 
@@ -377,23 +1078,37 @@ fn main() {
             let transaction = sentry::start_transaction(tx_ctx);
             sentry::configure_scope(|scope| scope.set_span(Some(transaction.clone().into())));
 
-            // Use Tauri's app_data_dir for user-writable storage
-            let data_path = app.path().app_data_dir()
-                .expect("failed to resolve app data dir")
-                .join("storage");
+            // Storage folder from the settings, else Tauri's app_data_dir
+            let settings = settings::get(app.handle());
+            let data_path = match &settings.data_path {
+                Some(path) => PathBuf::from(path),
+                None => app.path().app_data_dir()
+                    .expect("failed to resolve app data dir")
+                    .join("storage"),
+            };
+
+            // Website names are scrubbed from telemetry
+            for project in recent::list(app.handle()) {
+                telemetry::add_sensitive_term(&project.name);
+            }
 
             // On first launch, ask the user for telemetry consent.
-            // The choice is saved and takes effect on next launch.
-            let consent_dir = app.path().app_data_dir()
-                .expect("failed to resolve app data dir");
-            if option_env!("GLITCHTIP_DSN").is_some()
-                && read_telemetry_consent(&consent_dir).is_none()
-            {
-                prompt_telemetry_consent(app.handle(), consent_dir);
+            // The choice is saved in the settings.
+            if telemetry::configured_dsn(&settings).is_some() && settings.telemetry.is_none() {
+                telemetry::prompt_consent(app.handle());
             }
 
-            // Show splash screen while the app loads
-            let _splash = WebviewWindowBuilder::new(
+            // Saved window geometry (read by the menu, splash and editor windows)
+            app.manage(window_state::load(app.handle()));
+
+            // Native application menu
+            let app_menu = menu::build(app.handle())?;
+            app.set_menu(app_menu)?;
+            app.on_menu_event(menu::on_menu_event);
+
+            // Show splash screen while the app loads, on the monitor Silex
+            // was last used on
+            let splash = WebviewWindowBuilder::new(
                 app,
                 "splash",
                 WebviewUrl::App("splash.html".into()),
@@ -402,68 +1117,158 @@ fn main() {
             .inner_size(400.0, 300.0)
             .resizable(false)
             .decorations(false)
-            .center()
-            .always_on_top(true)
+            .always_on_top(true);
+            let _splash = match window_state::centered_on_saved_monitor(app.handle(), 400.0, 300.0) {
+                Some((x, y)) => splash.position(x, y),
+                None => splash.center(),
+            }
             .build()?;
 
             let pending_evals = mcp::PendingEvals::default();
-            let port = tauri::async_runtime::block_on(start_server(pending_evals.clone(), data_path));
+            let publish_progress = mcp::PublishProgress::default();
+            app.manage(shutdown::Shutdown::new(pending_evals.clone()));
+            let port = tauri::async_runtime::block_on(start_server(
+                app.handle().clone(),
+                pending_evals.clone(),
+                publish_progress.clone(),
+                data_path,
+            ));
+            app.manage(ServerInfo { port });
 
-            let url = format!("http://localhost:{}/", port);
+            // --open <website_id> launches straight into an existing project,
+            // other launch actions (unknown ids included, to report them) run
+            // once the main window exists.
+            // Without launch actions, optionally reopen the last project.
+            let args: Vec<String> = std::env::args().collect();
+            let cwd = std::env::current_dir().unwrap_or_default();
+            let mut actions = launch::parse_args(&args, &cwd);
+            let website_id = match actions.first() {
+                Some(launch::LaunchAction::Open(id)) if launch::website_exists(app.handle(), id) => {
+                    let id = id.clone();
+                    actions.remove(0);
+                    Some(id)
+                }
+                None if settings.reopen_last_project => recent::list(app.handle())
+                    .into_iter()
+                    .next()
+                    .map(|p| p.website_id)
+                    .filter(|id| launch::website_exists(app.handle(), id)),
+                _ => None,
+            };
+            let url = match website_id {
+                Some(id) => format!(
+                    "http://localhost:{}/?id={}",
+                    port,
+                    url::form_urlencoded::byte_serialize(id.as_bytes()).collect::<String>()
+                ),
+                None => format!("http://localhost:{}/", port),
+            };
             let app_handle_for_splash = app.handle().clone();
-            let window = WebviewWindowBuilder::new(
-                app,
-                "main",
-                WebviewUrl::External(url.parse().unwrap()),
-            )
-            .title("Silex")
-            .maximized(true)
-            .initialization_script(include_str!("../scripts/desktop-bridge.js"))
-            .on_page_load(move |webview, payload| {
-                if matches!(payload.event(), tauri::webview::PageLoadEvent::Finished) {
-                    // Close splash — main window is already maximized behind it
-                    if let Some(splash) = app_handle_for_splash.get_webview_window("splash") {
-                        let _ = splash.close();
+            let window = editor_window_builder(app.handle(), "main", &url)?
+                .visible(false)
+                .on_page_load(move |webview, payload| {
+                    if matches!(payload.event(), tauri::webview::PageLoadEvent::Finished) {
+                        // Close splash — main window is already shown behind it
+                        if let Some(splash) = app_handle_for_splash.get_webview_window("splash") {
+                            let _ = splash.close();
+                        }
+                        let _ = webview.set_focus();
                     }
-                    let _ = webview.set_focus();
+                })
+                .build()?;
+            // Reopen where it was left, maximized on first launch
+            if !window_state::restore(&window) {
+                let _ = window.maximize();
+            }
+            window.show()?;
+            // Track focus and geometry, handle close with unsaved changes
+            watch_editor_window(&window);
+            launch::run(app.handle(), actions);
+
+            // Unsaved changes left by a crash
+            recovery::offer_restore(app.handle());
+
+            // silex:// links: installers register the scheme, but AppImages and
+            // dev builds need it at runtime. On Linux and Windows links arrive
+            // as launch arguments (above, or forwarded by a second instance);
+            // on macOS they are delivered to the running app.
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+
+                #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+                if let Err(e) = app.deep_link().register_all() {
+                    tracing::warn!("Failed to register silex:// links: {}", e);
                 }
-            })
-            .build()?;
+                let deep_link_handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    launch::focus(&deep_link_handle);
+                    let actions = event
+                        .urls()
+                        .into_iter()
+                        .map(|url| launch::LaunchAction::DeepLink(url.to_string()))
+                        .collect();
+                    launch::run(&deep_link_handle, actions);
+                });
+                // The link that launched the app is not replayed to on_open_url
+                #[cfg(target_os = "macos")]
+                match app.deep_link().get_current() {
+                    Ok(Some(urls)) => launch::run(
+                        app.handle(),
+                        urls.into_iter()
+                            .map(|url| launch::LaunchAction::DeepLink(url.to_string()))
+                            .collect(),
+                    ),
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to read the silex:// launch link: {}", e),
+                }
+            }
 
             // MCP transport: --stdio for agent-managed launch, HTTP otherwise
+            app.manage(mcp::McpState::default());
             if std::env::args().any(|a| a == "--stdio") {
                 let mcp_handle = app.handle().clone();
                 tauri::async_runtime::spawn(async move {
-                    mcp::start_mcp_stdio(mcp_handle, pending_evals).await;
+                    mcp::start_mcp_stdio(mcp_handle, pending_evals, publish_progress).await;
                 });
             } else {
-                let mcp_handle = app.handle().clone();
-                tauri::async_runtime::spawn(async move {
-                    mcp::start_mcp_server(mcp_handle, pending_evals, 6807).await;
-                });
+                app.manage(mcp::McpHttp::new(pending_evals, publish_progress, settings.mcp_port));
+                if settings.mcp_enabled {
+                    let mcp_handle = app.handle().clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = mcp::start_mcp_server(mcp_handle).await {
+                            tracing::error!("MCP server not started: {}", e);
+                        }
+                    });
+                }
+            }
+
+            // Tray icon with server and MCP status
+            if let Err(e) = tray::build(app.handle()) {
+                tracing::warn!("Failed to create tray icon: {}", e);
             }
 
+            // Apply edits made to settings.json outside of the app
+            settings::watch(app.handle());
+
             // Finish the startup transaction (sends to GlitchTip Performance)
             transaction.finish();
 
             // Check for updates in the background
             check_for_updates(app.handle().clone());
 
-            // Handle window close with unsaved changes
-            let app_handle = app.handle().clone();
-            window.on_window_event(move |event| {
-                if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                    let state = app_handle.state::<AppState>();
-                    let has_changes = *state.has_unsaved_changes.lock().unwrap();
-                    if has_changes {
-                        api.prevent_close();
-                        show_quit_dialog(&app_handle);
-                    }
-                }
-            });
-
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            // Closing the last window exits, unless Silex keeps running in the tray
+            tauri::RunEvent::ExitRequested { api, code: None, .. } => {
+                if tray::keeps_running(app) {
+                    api.prevent_exit();
+                }
+            }
+            // Stop servers and pending work before the process ends
+            tauri::RunEvent::Exit => shutdown::run(app),
+            _ => {}
+        });
 }
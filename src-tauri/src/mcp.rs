@@ -1,10 +1,14 @@
 /*
  * MCP (Model Context Protocol) server for Silex Desktop.
  *
- * Static tools: website, take_screenshot.
+ * Static tools: website, take_screenshot, publish, preview, list_windows,
+ * select_window, get_console_logs, get_app_state.
  * Dynamic tools: loaded from the grapesjs-ai-capabilities registry
  * after a project is opened. Each capability becomes an MCP tool
  * that calls editor.runCommand() via eval_js.
+ *
+ * Tools act on the focused editor window, unless the session selected
+ * another one with select_window.
  */
 
 use std::collections::HashMap;
@@ -18,13 +22,15 @@ use rmcp::model::*;
 use rmcp::schemars::JsonSchema;
 use rmcp::service::RequestContext;
 use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
-use rmcp::transport::streamable_http_server::StreamableHttpService;
-use rmcp::{tool, tool_router, ErrorData as McpError, RoleServer, ServerHandler, ServiceExt};
+use rmcp::transport::streamable_http_server::session::SessionManager;
+use rmcp::transport::streamable_http_server::{StreamableHttpServerConfig, StreamableHttpService};
+use rmcp::{tool, tool_router, ErrorData as McpError, Peer, RoleServer, ServerHandler, ServiceExt};
 use serde::Deserialize;
 use tauri::Manager;
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
 
-use crate::AppState;
+use crate::{AppState, WindowProject};
 
 // ==========================================================================
 // Shared state
@@ -33,6 +39,42 @@ use crate::AppState;
 /// Pending eval results — shared between MCP tools and the HTTP callback handler.
 pub type PendingEvals = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<String>>>>;
 
+/// Publication progress events — shared between the publish tool and the HTTP progress handler.
+pub type PublishProgress = Arc<std::sync::Mutex<HashMap<u64, mpsc::UnboundedSender<String>>>>;
+
+/// MCP state that outlives sessions and restarts of the HTTP transport.
+#[derive(Default)]
+pub struct McpState {
+    /// Eval ids, unique while the app runs (pending evals outlive servers).
+    eval_counter: AtomicU64,
+    /// Editor tools loaded from each window's capabilities registry, by
+    /// window label: every session targeting a window sees its tools.
+    /// Routers are shared so calls run without holding the lock.
+    window_tools: tokio::sync::RwLock<HashMap<String, Arc<ToolRouter<SilexMcp>>>>,
+}
+
+/// Forget the editor tools of a closed window.
+pub async fn forget_window(app: &tauri::AppHandle, label: &str) {
+    if let Some(state) = app.try_state::<McpState>() {
+        state.window_tools.write().await.remove(label);
+    }
+}
+
+/// A publication without progress events for this long is given up.
+const PUBLISH_IDLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// Removes a pending eval when dropped.
+struct PendingEval<'a> {
+    pending: &'a PendingEvals,
+    id: u64,
+}
+
+impl Drop for PendingEval<'_> {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(&self.id);
+    }
+}
+
 // ==========================================================================
 // Action enums (static tools only)
 // ==========================================================================
@@ -41,12 +83,15 @@ pub type PendingEvals = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<String
 #[serde(rename_all = "snake_case")]
 pub enum WebsiteAction {
     List,
+    Get,
     Create,
     Delete,
     Rename,
     Duplicate,
     Open,
     Dashboard,
+    Templates,
+    SaveAsTemplate,
 }
 
 // ==========================================================================
@@ -57,10 +102,26 @@ pub enum WebsiteAction {
 pub struct WebsiteParams {
     /// The action to perform.
     pub action: WebsiteAction,
-    /// Website ID (required for delete, rename, duplicate, open).
+    /// Website ID (required for get, delete, rename, duplicate, open, save_as_template).
     pub website_id: Option<String>,
-    /// Website name (required for create, rename).
+    /// Website name (required for rename, save_as_template; optional for create).
     pub name: Option<String>,
+    /// Template ID to create the website from (create only, see action 'templates').
+    pub template: Option<String>,
+    /// Template description (save_as_template only).
+    pub description: Option<String>,
+    /// Filter by name: substring or fuzzy match (list only).
+    pub query: Option<String>,
+    /// Sort order (list only): "updated" or "created" (newest first), or "name".
+    /// Without sort or query, websites are ordered by id.
+    pub sort: Option<crate::websites::SortOrder>,
+    /// Maximum number of websites to return (list only).
+    pub limit: Option<usize>,
+    /// Pagination cursor, from the nextCursor of a previous list response
+    /// with the same query and sort.
+    pub cursor: Option<String>,
+    /// Only return websiteId, name and updatedAt for each website (list only).
+    pub compact: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -72,21 +133,55 @@ pub struct ScreenshotParams {
     pub output_file: Option<String>,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PreviewParams {
+    /// Website ID (defaults to the open project).
+    pub website_id: Option<String>,
+    /// Reload preview pages automatically after each publish (default: true).
+    pub live_reload: Option<bool>,
+    /// Stop the preview server instead of starting it.
+    pub stop: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PublishParams {
+    /// List the files that would be written, without publishing.
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SelectWindowParams {
+    /// Window label from list_windows. Omit to follow the focused window again.
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetConsoleLogsParams {
+    /// Least severe level returned: error, warn, info, log or debug (default: log).
+    pub level: Option<crate::console::ConsoleLevel>,
+    /// Only entries after this timestamp (milliseconds since the epoch, e.g. of the last entry seen).
+    pub since: Option<u64>,
+    /// Maximum number of entries, the most recent ones (default: 50).
+    pub limit: Option<usize>,
+    /// Entries of every editor window instead of the targeted one.
+    pub all_windows: Option<bool>,
+}
+
 // ==========================================================================
 // SilexMcp struct
 // ==========================================================================
 
 #[derive(Clone)]
 pub struct SilexMcp {
-    /// Static tools (website, take_screenshot) generated by #[tool_router].
+    /// Static tools (website, take_screenshot, publish, preview, list_windows, select_window, get_console_logs, get_app_state) generated by #[tool_router].
     tool_router: ToolRouter<Self>,
-    /// Dynamic tools loaded from the grapesjs-ai-capabilities JS registry.
-    dynamic_tools: Arc<tokio::sync::RwLock<ToolRouter<Self>>>,
-    /// Whether dynamic capabilities have been loaded for this session.
-    capabilities_loaded: Arc<std::sync::atomic::AtomicBool>,
     app_handle: tauri::AppHandle,
-    eval_counter: Arc<AtomicU64>,
     pending_evals: PendingEvals,
+    publish_progress: PublishProgress,
+    /// Editor window chosen with select_window (None = follow focus). Per session.
+    selected_window: Arc<std::sync::Mutex<Option<String>>>,
+    /// Whether this session's peer is known to the shutdown (to notify it).
+    peer_registered: Arc<std::sync::atomic::AtomicBool>,
 }
 
 // ==========================================================================
@@ -95,37 +190,68 @@ pub struct SilexMcp {
 
 impl SilexMcp {
     fn get_base_url(&self) -> String {
-        self.app_handle
-            .get_webview_window("main")
-            .and_then(|w| w.url().ok())
-            .map(|u| {
+        crate::base_url(&self.app_handle)
+    }
+
+    /// Editor window the tools act on: the selected one, or the focused one.
+    fn target_window(&self) -> Result<tauri::WebviewWindow, String> {
+        let selected = self.selected_window.lock().unwrap().clone();
+        match selected {
+            Some(label) => self.app_handle.get_webview_window(&label).ok_or_else(|| {
                 format!(
-                    "{}://{}:{}",
-                    u.scheme(),
-                    u.host_str().unwrap_or("localhost"),
-                    u.port().unwrap_or(6805)
+                    "Window '{}' was closed. Use list_windows and select_window to pick another one.",
+                    label
                 )
-            })
-            .unwrap_or_else(|| "http://localhost:6805".to_string())
+            }),
+            None => crate::focused_editor(&self.app_handle).ok_or_else(|| "No editor window".to_string()),
+        }
+    }
+
+    /// Project open in the target window.
+    fn target_project(&self) -> WindowProject {
+        self.target_window()
+            .map(|w| self.app_handle.state::<AppState>().project(w.label()))
+            .unwrap_or_default()
+    }
+
+    fn state(&self) -> tauri::State<'_, McpState> {
+        self.app_handle.state::<McpState>()
+    }
+
+    fn next_eval_id(&self) -> u64 {
+        self.state().eval_counter.fetch_add(1, Ordering::Relaxed)
     }
 
-    /// Navigate the webview to a URL (safe, no injection).
+    /// Whether the editor tools of the target window are loaded.
+    async fn capabilities_loaded(&self) -> bool {
+        match self.target_window() {
+            Ok(window) => self.state().window_tools.read().await.contains_key(window.label()),
+            Err(_) => false,
+        }
+    }
+
+    /// Editor tools of the target window.
+    async fn dynamic_tools(&self) -> Vec<Tool> {
+        let Ok(window) = self.target_window() else { return Vec::new() };
+        let state = self.state();
+        let tools = state.window_tools.read().await;
+        tools.get(window.label()).map(|router| router.list_all()).unwrap_or_default()
+    }
+
+    /// Forget the editor tools of a window leaving its project; they are
+    /// loaded again for the next one.
+    async fn clear_capabilities(&self, label: &str) {
+        self.state().window_tools.write().await.remove(label);
+    }
+
+    /// Navigate the target window to a URL (safe, no injection).
     fn navigate_to(&self, url: &str) -> Result<(), String> {
-        let window = self
-            .app_handle
-            .get_webview_window("main")
-            .ok_or_else(|| "No main window".to_string())?;
-        let url_json =
-            serde_json::to_string(url).map_err(|e| format!("Failed to encode URL: {}", e))?;
-        window
-            .eval(&format!("window.location.href = {}", url_json))
-            .map_err(|e| format!("Navigation failed: {}", e))
+        crate::navigate_window(&self.target_window()?, url)
     }
 
     /// Check that a project is open.
     fn require_project(&self) -> Result<(), String> {
-        let state = self.app_handle.state::<AppState>();
-        if state.current_website_id.lock().unwrap().is_none() {
+        if self.target_project().website_id.is_none() {
             return Err(
                 "No project open. Use website(action: 'open') or website(action: 'create') first."
                     .into(),
@@ -140,10 +266,7 @@ impl SilexMcp {
         js_code: &str,
         timeout_secs: u64,
     ) -> Result<Option<String>, String> {
-        let window = self
-            .app_handle
-            .get_webview_window("main")
-            .ok_or_else(|| "No main window".to_string())?;
+        let window = self.target_window()?;
 
         if let Ok(url) = window.url() {
             let path = url.path();
@@ -156,9 +279,19 @@ impl SilexMcp {
             }
         }
 
-        let id = self.eval_counter.fetch_add(1, Ordering::Relaxed);
+        let id = self.next_eval_id();
         let (tx, rx) = oneshot::channel::<String>();
-        self.pending_evals.lock().unwrap().insert(id, tx);
+        {
+            // Checked under the lock shutdown drains pending evals with, so
+            // an eval is either failed by it or never starts
+            let mut pending = self.pending_evals.lock().unwrap();
+            if self.app_handle.try_state::<crate::shutdown::Shutdown>().is_some_and(|s| s.is_shutting_down()) {
+                return Err("app shutting down".into());
+            }
+            pending.insert(id, tx);
+        }
+        // Forget the eval if we stop waiting (timeout, caller gave up)
+        let _pending = PendingEval { pending: &self.pending_evals, id };
 
         let js_escaped = serde_json::to_string(js_code)
             .map_err(|e| format!("Failed to escape JS: {}", e))?;
@@ -167,18 +300,17 @@ impl SilexMcp {
             .replace("__JS__", &js_escaped)
             .replace("__ID__", &id.to_string());
 
-        window.eval(&wrapped).map_err(|e| {
-            self.pending_evals.lock().unwrap().remove(&id);
-            format!("Failed to inject JS: {}", e)
-        })?;
+        let started = std::time::Instant::now();
+        window.eval(&wrapped).map_err(|e| format!("Failed to inject JS: {}", e))?;
 
         let raw = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), rx)
             .await
             .map_err(|_| {
-                self.pending_evals.lock().unwrap().remove(&id);
+                crate::metrics::record_eval_timeout();
                 format!("Timeout waiting for JS result ({}s)", timeout_secs)
             })?
             .map_err(|_| "Internal error: JS callback channel closed unexpectedly. Retry the operation.".to_string())?;
+        crate::metrics::record_eval(started.elapsed());
 
         #[derive(Deserialize)]
         struct JsResult {
@@ -197,6 +329,111 @@ impl SilexMcp {
         }
     }
 
+    /// Run a static or dynamic tool.
+    async fn route_tool_call(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        // Check which router owns this tool before consuming request
+        if self.tool_router.get(&request.name).is_some() {
+            let is_website_tool = request.name.as_ref() == "website";
+            let caps_before = self.capabilities_loaded().await;
+            let peer = context.peer.clone();
+
+            let tool_ctx = rmcp::handler::server::tool::ToolCallContext::new(
+                self, request, context,
+            );
+            let result = self.tool_router.call(tool_ctx).await;
+
+            // If the website tool just loaded capabilities, notify the client
+            if is_website_tool && !caps_before && self.capabilities_loaded().await {
+                if let Err(e) = peer.notify_tool_list_changed().await {
+                    tracing::warn!("Failed to send tools/list_changed: {}", e);
+                }
+            }
+
+            return result;
+        }
+
+        if let Ok(window) = self.target_window() {
+            // Release the lock before the call: evals can take seconds, and
+            // a window loading its tools would wait for the write lock,
+            // blocking every other call behind it.
+            let dynamic = self
+                .state()
+                .window_tools
+                .read()
+                .await
+                .get(window.label())
+                .filter(|d| d.has_route(&request.name))
+                .cloned();
+            if let Some(dynamic) = dynamic {
+                let tool_ctx = rmcp::handler::server::tool::ToolCallContext::new(
+                    self, request, context,
+                );
+                return dynamic.call(tool_ctx).await;
+            }
+        }
+
+        Err(McpError::invalid_params(
+            format!("Tool '{}' not found. Use list_tools to see available tools.", request.name),
+            None,
+        ))
+    }
+
+    /// Capture the editor ("ui") or the website canvas ("canvas") as PNG.
+    /// Returns the base64 data and the decoded bytes.
+    async fn capture_screenshot(&self, target: &str) -> Result<(String, Vec<u8>), String> {
+        let screenshot_js = r#"
+(async function() {
+    if (!window.html2canvas) {
+        const s = document.createElement('script');
+        s.src = 'https://cdnjs.cloudflare.com/ajax/libs/html2canvas/1.4.1/html2canvas.min.js';
+        await new Promise((resolve, reject) => {
+            s.onload = resolve;
+            s.onerror = () => reject(new Error('Failed to load html2canvas from CDN'));
+            document.head.appendChild(s);
+        });
+    }
+    let element;
+    if ('__TARGET__' === 'canvas') {
+        const frame = document.querySelector('.gjs-frame');
+        if (frame && frame.contentDocument && frame.contentDocument.body) {
+            element = frame.contentDocument.body;
+        } else {
+            throw new Error('GrapesJS canvas iframe not found or not accessible');
+        }
+    } else {
+        element = document.body;
+    }
+    const canvas = await html2canvas(element, { useCORS: true, allowTaint: true });
+    return canvas.toDataURL('image/png');
+})()
+"#
+        .replace("__TARGET__", target);
+
+        let data_url = match self.eval_js_internal(&screenshot_js, 30).await {
+            Ok(Some(url)) => url,
+            Ok(None) => return Err("Screenshot returned no data".into()),
+            Err(e) => return Err(format!("Screenshot failed: {}", e)),
+        };
+
+        let base64_prefix = "data:image/png;base64,";
+        let base64_data = if data_url.starts_with(base64_prefix) {
+            &data_url[base64_prefix.len()..]
+        } else {
+            return Err("Unexpected data URL format".into());
+        };
+
+        let png_bytes = match base64::engine::general_purpose::STANDARD.decode(base64_data) {
+            Ok(bytes) => bytes,
+            Err(e) => return Err(format!("Failed to decode base64: {}", e)),
+        };
+
+        Ok((base64_data.to_string(), png_bytes))
+    }
+
     // ======================================================================
     // Dynamic capability loading
     // ======================================================================
@@ -204,6 +441,7 @@ impl SilexMcp {
     /// Load capabilities from the editor's JS registry and add as dynamic MCP tools.
     /// Called after a website is opened/created and the editor is ready.
     async fn load_capabilities(&self) -> Result<usize, String> {
+        let label = self.target_window()?.label().to_string();
         // Wait for capabilities to be registered (poll up to 30s).
         // The registry function exists early, but capabilities are registered
         // after the editor 'load' event fires 'ai-capabilities:ready'.
@@ -346,9 +584,8 @@ impl SilexMcp {
             router.add_route(route);
         }
 
-        // Replace the dynamic tools router
-        *self.dynamic_tools.write().await = router;
-        self.capabilities_loaded.store(true, Ordering::Release);
+        // Replace the window's dynamic tools router
+        self.state().window_tools.write().await.insert(label, Arc::new(router));
 
         tracing::info!("Loaded {} dynamic capabilities as MCP tools", count);
         Ok(count)
@@ -371,25 +608,30 @@ fn tool_error(msg: impl Into<String>) -> CallToolResult {
 
 #[tool_router]
 impl SilexMcp {
-    pub fn new(
-        app_handle: tauri::AppHandle,
-        pending_evals: PendingEvals,
-        eval_counter: Arc<AtomicU64>,
-        dynamic_tools: Arc<tokio::sync::RwLock<ToolRouter<Self>>>,
-        capabilities_loaded: Arc<std::sync::atomic::AtomicBool>,
-    ) -> Self {
+    /// Dynamic tools and eval ids live in the managed `McpState`.
+    pub fn new(app_handle: tauri::AppHandle, pending_evals: PendingEvals, publish_progress: PublishProgress) -> Self {
         Self {
             tool_router: Self::tool_router(),
-            dynamic_tools,
-            capabilities_loaded,
             app_handle,
-            eval_counter,
             pending_evals,
+            publish_progress,
+            selected_window: Arc::new(std::sync::Mutex::new(None)),
+            peer_registered: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        }
+    }
+
+    /// Have the session notified when the app shuts down.
+    fn register_peer(&self, peer: &Peer<RoleServer>) {
+        if self.peer_registered.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        if let Some(shutdown) = self.app_handle.try_state::<crate::shutdown::Shutdown>() {
+            shutdown.add_peer(peer.clone());
         }
     }
 
     /// Start a Sentry transaction for an MCP tool call.
-    fn start_tool_transaction(tool_name: &str, action: &str) -> sentry::TransactionOrSpan {
+    pub(crate) fn start_tool_transaction(tool_name: &str, action: &str) -> sentry::TransactionOrSpan {
         let tx_ctx = sentry::TransactionContext::new(
             &format!("mcp/{}", tool_name),
             "mcp.tool",
@@ -403,7 +645,7 @@ impl SilexMcp {
     // website — list, create, delete, rename, duplicate, open, dashboard
     // ----------------------------------------------------------------------
 
-    #[tool(description = "Manage websites in the Silex visual website builder. Actions: list, create, delete, rename, duplicate, open, dashboard. After create or open, new editor tools are loaded dynamically — call list_tools to discover them. Use dashboard to return to the website list.")]
+    #[tool(description = "Manage websites in the Silex visual website builder. Actions: list, get, create, delete, rename, duplicate, open, dashboard, templates, save_as_template. List accepts query, sort, limit, cursor and compact to keep results small; get returns one website's metadata with page count, asset count and size on disk. Pass template to create to start from a template (see templates). After create or open, new editor tools are loaded dynamically — call list_tools to discover them. Use dashboard to return to the website list.")]
     async fn website(
         &self,
         Parameters(params): Parameters<WebsiteParams>,
@@ -415,14 +657,94 @@ impl SilexMcp {
         match params.action {
             WebsiteAction::List => {
                 let url = format!("{}/api/website", base_url);
-                match reqwest::get(&url).await {
+                let body = match reqwest::get(&url).await {
                     Ok(resp) => match resp.text().await {
-                        Ok(body) => {
-                            Ok(CallToolResult::success(vec![Content::text(body)]))
-                        }
-                        Err(e) => Ok(tool_error(format!("Error reading response: {}", e))),
+                        Ok(body) => body,
+                        Err(e) => return Ok(tool_error(format!("Error reading response: {}", e))),
                     },
-                    Err(e) => Ok(tool_error(format!("Error fetching websites: {}", e))),
+                    Err(e) => return Ok(tool_error(format!("Error fetching websites: {}", e))),
+                };
+                let query = crate::websites::ListQuery {
+                    query: params.query.as_deref(),
+                    sort: params.sort,
+                    limit: params.limit,
+                    cursor: params.cursor.as_deref(),
+                    compact: params.compact.unwrap_or(false),
+                };
+                // No query parameters: return the server's list unchanged
+                if query.is_empty() {
+                    return Ok(CallToolResult::success(vec![Content::text(body)]));
+                }
+                let sites: Vec<serde_json::Value> = match serde_json::from_str(&body) {
+                    Ok(sites) => sites,
+                    Err(e) => return Ok(tool_error(format!("Unexpected website list: {}", e))),
+                };
+                match crate::websites::query_websites(sites, &query) {
+                    Ok(result) => Ok(CallToolResult::success(vec![Content::text(result.to_string())])),
+                    Err(e) => Err(McpError::invalid_params(e, None)),
+                }
+            }
+
+            WebsiteAction::Get => {
+                let wid = params
+                    .website_id
+                    .as_deref()
+                    .ok_or_else(|| McpError::invalid_params("website_id is required", None))?;
+                let site_dir = crate::websites::site_dir(&self.app_handle, wid)
+                    .map_err(|e| McpError::invalid_params(e, None))?;
+                let url = format!(
+                    "{}/api/website/meta?websiteId={}&connectorId=fs-storage",
+                    base_url,
+                    url::form_urlencoded::byte_serialize(wid.as_bytes()).collect::<String>()
+                );
+                let mut meta = match client.get(&url).send().await {
+                    Ok(resp) if resp.status().is_success() => {
+                        match resp.json::<serde_json::Value>().await {
+                            Ok(meta) => meta,
+                            Err(e) => return Ok(tool_error(format!("Error reading response: {}", e))),
+                        }
+                    }
+                    Ok(resp) => {
+                        let body = resp.text().await.unwrap_or_default();
+                        return Ok(tool_error(format!("Error fetching website '{}': {}", wid, body)));
+                    }
+                    Err(e) => return Ok(tool_error(format!("Error fetching website: {}", e))),
+                };
+                if let (Some(meta), serde_json::Value::Object(stats)) =
+                    (meta.as_object_mut(), crate::websites::website_stats(&site_dir))
+                {
+                    meta.insert("websiteId".into(), wid.into());
+                    meta.extend(stats);
+                }
+                Ok(CallToolResult::success(vec![Content::text(meta.to_string())]))
+            }
+
+            WebsiteAction::Create if params.template.is_some() => {
+                let template = params.template.as_deref().unwrap_or_default();
+                match crate::templates::create_from_template(
+                    &self.app_handle,
+                    &base_url,
+                    template,
+                    params.name.as_deref(),
+                )
+                .await
+                {
+                    Ok(id) => {
+                        if let Err(e) = self.navigate_to(&format!("{}/?id={}", base_url, id)) {
+                            return Ok(tool_error(format!(
+                                "Website {} created from template '{}', but opening it failed: {}",
+                                id, template, e
+                            )));
+                        }
+                        match self.load_capabilities().await {
+                            Ok(n) => tracing::info!("Loaded {} capabilities after create", n),
+                            Err(e) => tracing::warn!("Failed to load capabilities: {}", e),
+                        }
+                        Ok(CallToolResult::success(vec![Content::text(
+                            serde_json::json!({ "websiteId": id, "template": template }).to_string()
+                        )]))
+                    }
+                    Err(e) => Ok(tool_error(e)),
                 }
             }
 
@@ -484,15 +806,23 @@ impl SilexMcp {
                     .ok_or_else(|| McpError::invalid_params("website_id is required", None))?;
                 let url = format!(
                     "{}/api/website?websiteId={}&connectorId=fs-storage",
-                    base_url, wid
+                    base_url,
+                    url::form_urlencoded::byte_serialize(wid.as_bytes()).collect::<String>()
                 );
                 match client.delete(&url).send().await {
                     Ok(resp) => {
                         if resp.status().is_success() {
-                            let _ = self.navigate_to(&format!("{}/", base_url));
-                            // Clear dynamic tools since we're back on dashboard
-                            *self.dynamic_tools.write().await = ToolRouter::new();
-                            self.capabilities_loaded.store(false, Ordering::Release);
+                            crate::recent::remove(&self.app_handle, wid);
+                            crate::recovery::clear(&self.app_handle, wid);
+                            crate::menu::refresh_recent(&self.app_handle);
+                            // Windows editing the deleted site go back to the dashboard
+                            let state = self.app_handle.state::<AppState>();
+                            for window in crate::editor_windows(&self.app_handle) {
+                                if state.project(window.label()).website_id.as_deref() == Some(wid) {
+                                    let _ = crate::navigate_window(&window, &format!("{}/", base_url));
+                                    self.clear_capabilities(window.label()).await;
+                                }
+                            }
                             Ok(CallToolResult::success(vec![Content::text(
                                 format!("{{\"success\":true,\"message\":\"Website '{}' deleted\"}}", wid)
                             )]))
@@ -586,8 +916,9 @@ impl SilexMcp {
 
             WebsiteAction::Dashboard => {
                 // Clear dynamic tools since we're leaving the editor
-                *self.dynamic_tools.write().await = ToolRouter::new();
-                self.capabilities_loaded.store(false, Ordering::Release);
+                if let Ok(window) = self.target_window() {
+                    self.clear_capabilities(window.label()).await;
+                }
                 match self.navigate_to(&format!("{}/", base_url)) {
                     Ok(_) => {
                         Ok(CallToolResult::success(vec![Content::text(
@@ -597,6 +928,50 @@ impl SilexMcp {
                     Err(e) => Ok(tool_error(e)),
                 }
             },
+
+            WebsiteAction::Templates => {
+                let templates = crate::templates::list_templates(&self.app_handle);
+                match serde_json::to_string(&templates) {
+                    Ok(json) => Ok(CallToolResult::success(vec![Content::text(json)])),
+                    Err(e) => Ok(tool_error(format!("Error listing templates: {}", e))),
+                }
+            }
+
+            WebsiteAction::SaveAsTemplate => {
+                let wid = params
+                    .website_id
+                    .as_deref()
+                    .ok_or_else(|| McpError::invalid_params("website_id is required", None))?;
+                let name = params
+                    .name
+                    .as_deref()
+                    .ok_or_else(|| McpError::invalid_params("name is required", None))?;
+                // Thumbnail from the canvas, only if this site is open in the editor
+                let is_open = self.target_project().website_id.as_deref() == Some(wid);
+                let preview = if is_open {
+                    match self.capture_screenshot("canvas").await {
+                        Ok((_, png)) => Some(png),
+                        Err(e) => {
+                            tracing::warn!("Template preview failed: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+                match crate::templates::save_as_template(
+                    &self.app_handle,
+                    wid,
+                    name,
+                    params.description.as_deref().unwrap_or(""),
+                    preview.as_deref(),
+                ) {
+                    Ok(meta) => Ok(CallToolResult::success(vec![Content::text(
+                        serde_json::to_string(&meta).unwrap_or_default()
+                    )])),
+                    Err(e) => Ok(tool_error(e)),
+                }
+            }
         }
     }
 
@@ -613,54 +988,13 @@ impl SilexMcp {
         let _tx = Self::start_tool_transaction("screenshot", params.target.as_deref().unwrap_or("ui"));
         let target = params.target.as_deref().unwrap_or("ui");
 
-        let screenshot_js = r#"
-(async function() {
-    if (!window.html2canvas) {
-        const s = document.createElement('script');
-        s.src = 'https://cdnjs.cloudflare.com/ajax/libs/html2canvas/1.4.1/html2canvas.min.js';
-        await new Promise((resolve, reject) => {
-            s.onload = resolve;
-            s.onerror = () => reject(new Error('Failed to load html2canvas from CDN'));
-            document.head.appendChild(s);
-        });
-    }
-    let element;
-    if ('__TARGET__' === 'canvas') {
-        const frame = document.querySelector('.gjs-frame');
-        if (frame && frame.contentDocument && frame.contentDocument.body) {
-            element = frame.contentDocument.body;
-        } else {
-            throw new Error('GrapesJS canvas iframe not found or not accessible');
-        }
-    } else {
-        element = document.body;
-    }
-    const canvas = await html2canvas(element, { useCORS: true, allowTaint: true });
-    return canvas.toDataURL('image/png');
-})()
-"#
-        .replace("__TARGET__", target);
-
-        let data_url = match self.eval_js_internal(&screenshot_js, 30).await {
-            Ok(Some(url)) => url,
-            Ok(None) => return Ok(tool_error("Screenshot returned no data")),
-            Err(e) => return Ok(tool_error(format!("Screenshot failed: {}", e))),
-        };
-
-        let base64_prefix = "data:image/png;base64,";
-        let base64_data = if data_url.starts_with(base64_prefix) {
-            &data_url[base64_prefix.len()..]
-        } else {
-            return Ok(tool_error("Unexpected data URL format"));
-        };
-
-        let png_bytes = match base64::engine::general_purpose::STANDARD.decode(base64_data) {
-            Ok(bytes) => bytes,
-            Err(e) => return Ok(tool_error(format!("Failed to decode base64: {}", e))),
+        let (base64_data, png_bytes) = match self.capture_screenshot(target).await {
+            Ok(shot) => shot,
+            Err(e) => return Ok(tool_error(e)),
         };
 
         // Build response with inline image
-        let mut content = vec![Content::image(base64_data.to_string(), "image/png")];
+        let mut content = vec![Content::image(base64_data, "image/png")];
 
         // Optionally save to file
         if let Some(path) = params.output_file {
@@ -673,10 +1007,285 @@ impl SilexMcp {
         Ok(CallToolResult::success(content))
     }
 
+    // ----------------------------------------------------------------------
+    // publish — runs the editor's publication, reports progress
+    // ----------------------------------------------------------------------
+
+    #[tool(description = "Publish the open website with its configured hosting. Returns the published URL, or the error details. Sends progress notifications while publishing when the request has a progressToken. Use dry_run to list the files that would be written without publishing.")]
+    async fn publish(
+        &self,
+        Parameters(params): Parameters<PublishParams>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let dry_run = params.dry_run.unwrap_or(false);
+        let _tx = Self::start_tool_transaction("publish", if dry_run { "dry_run" } else { "publish" });
+        if let Err(e) = self.require_project() {
+            return Ok(tool_error(e));
+        }
+
+        if dry_run {
+            let js = "window.__silexMcp.dryRunPublication(window.silex.getEditor())";
+            return match self.eval_js_internal(js, 60).await {
+                Ok(files) => Ok(CallToolResult::success(vec![Content::text(
+                    files.unwrap_or_else(|| "[]".into()),
+                )])),
+                Err(e) => Ok(tool_error(format!("Dry run failed: {}", e))),
+            };
+        }
+
+        let label = match self.target_window() {
+            Ok(window) => window.label().to_string(),
+            Err(e) => return Ok(tool_error(e)),
+        };
+        let website_id = self.target_project().website_id;
+
+        // Progress events are posted by the bridge to /publish-progress/{id}
+        // while the eval below waits for the final result.
+        let id = self.next_eval_id();
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        self.publish_progress.lock().unwrap().insert(id, tx);
+
+        let js = format!(
+            "window.__silexMcp.publish(window.silex.getEditor(), '/publish-progress/{}')",
+            id
+        );
+        let progress_token = meta.get_progress_token();
+        let publication = self.eval_js_internal(&js, 600);
+        tokio::pin!(publication);
+        let mut step = 0.0;
+        let mut idle_deadline = tokio::time::Instant::now() + PUBLISH_IDLE_TIMEOUT;
+        let mut window_check = tokio::time::interval(std::time::Duration::from_secs(1));
+        let result = loop {
+            tokio::select! {
+                result = &mut publication => break result,
+                _ = tokio::time::sleep_until(idle_deadline) => {
+                    break Err(format!(
+                        "No progress from the publication for {}s",
+                        PUBLISH_IDLE_TIMEOUT.as_secs()
+                    ));
+                }
+                _ = window_check.tick() => {
+                    // The editor closed or left the project: no result will come
+                    let gone = self.app_handle.get_webview_window(&label).is_none()
+                        || self.app_handle.state::<AppState>().project(&label).website_id != website_id;
+                    if gone {
+                        break Err("The editor window was closed during the publication".into());
+                    }
+                }
+                Some(event) = rx.recv() => {
+                    idle_deadline = tokio::time::Instant::now() + PUBLISH_IDLE_TIMEOUT;
+                    #[derive(Deserialize)]
+                    struct ProgressEvent {
+                        message: Option<String>,
+                        total: Option<f64>,
+                    }
+                    let Some(token) = progress_token.clone() else { continue };
+                    let event: ProgressEvent = match serde_json::from_str(&event) {
+                        Ok(event) => event,
+                        Err(_) => continue,
+                    };
+                    step += 1.0;
+                    let _ = peer
+                        .notify_progress(ProgressNotificationParam {
+                            progress_token: token,
+                            progress: step,
+                            total: event.total,
+                            message: event.message,
+                        })
+                        .await;
+                }
+            }
+        };
+        self.publish_progress.lock().unwrap().remove(&id);
+
+        match result {
+            Ok(Some(json)) => {
+                let failed = serde_json::from_str::<serde_json::Value>(&json)
+                    .ok()
+                    .and_then(|v| v.get("success").and_then(|s| s.as_bool()))
+                    == Some(false);
+                if failed {
+                    Ok(tool_error(json))
+                } else {
+                    Ok(CallToolResult::success(vec![Content::text(json)]))
+                }
+            }
+            Ok(None) => Ok(tool_error("Publication returned no result")),
+            Err(e) => Ok(tool_error(format!("Publication failed: {}", e))),
+        }
+    }
+
+    // ----------------------------------------------------------------------
+    // preview — serve the published output on localhost
+    // ----------------------------------------------------------------------
+
+    #[tool(description = "Serve a website's published output (local folder publication) on a localhost port and return the preview URL. Pages reload automatically after each publish unless live_reload is false. Use stop to shut the preview server down.")]
+    async fn preview(
+        &self,
+        Parameters(params): Parameters<PreviewParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let stop = params.stop.unwrap_or(false);
+        let _tx = Self::start_tool_transaction("preview", if stop { "stop" } else { "start" });
+        let website_id = match params.website_id {
+            Some(id) => id,
+            None => match self.target_project().website_id {
+                Some(id) => id,
+                None => return Err(McpError::invalid_params("website_id is required when no project is open", None)),
+            },
+        };
+
+        if stop {
+            let stopped = crate::preview::stop_preview(&self.app_handle, &website_id);
+            return Ok(CallToolResult::success(vec![Content::text(
+                serde_json::json!({ "success": true, "stopped": stopped }).to_string(),
+            )]));
+        }
+
+        match crate::preview::start_preview(&self.app_handle, &website_id, params.live_reload.unwrap_or(true)).await {
+            Ok(url) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::json!({ "success": true, "url": url }).to_string(),
+            )])),
+            Err(e) => Ok(tool_error(e)),
+        }
+    }
+
+    // ----------------------------------------------------------------------
+    // list_windows / select_window — choose the editor window to act on
+    // ----------------------------------------------------------------------
+
+    #[tool(description = "List the open editor windows with their label, title, open website and unsaved state. 'focused' is the window the user last used, 'selected' the one this session's tools act on.")]
+    async fn list_windows(&self) -> Result<CallToolResult, McpError> {
+        let _tx = Self::start_tool_transaction("list_windows", "list");
+        let state = self.app_handle.state::<AppState>();
+        let focused = crate::focused_editor(&self.app_handle).map(|w| w.label().to_string());
+        let target = self.target_window().ok().map(|w| w.label().to_string());
+        let windows: Vec<serde_json::Value> = crate::editor_windows(&self.app_handle)
+            .iter()
+            .map(|w| {
+                let label = w.label();
+                let project = state.project(label);
+                serde_json::json!({
+                    "label": label,
+                    "title": w.title().unwrap_or_default(),
+                    "websiteId": project.website_id,
+                    "websiteName": project.website_name,
+                    "unsaved": project.has_unsaved_changes,
+                    "focused": focused.as_deref() == Some(label),
+                    "selected": target.as_deref() == Some(label),
+                })
+            })
+            .collect();
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::Value::Array(windows).to_string(),
+        )]))
+    }
+
+    #[tool(description = "Select the editor window that website, take_screenshot, publish, preview and editor tools act on. Pass a label from list_windows, or no label to follow the focused window again. Editor tools follow the selected window: call list_tools afterwards.")]
+    async fn select_window(
+        &self,
+        Parameters(params): Parameters<SelectWindowParams>,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let _tx = Self::start_tool_transaction("select_window", params.label.as_deref().unwrap_or("focused"));
+        if let Some(label) = &params.label {
+            if !crate::is_editor_window(label) || self.app_handle.get_webview_window(label).is_none() {
+                return Err(McpError::invalid_params(
+                    format!("No editor window '{}'. Use list_windows to see open windows.", label),
+                    None,
+                ));
+            }
+        }
+        *self.selected_window.lock().unwrap() = params.label.clone();
+        // Editor tools are those of the newly targeted window (loaded on next list_tools)
+        if let Err(e) = peer.notify_tool_list_changed().await {
+            tracing::warn!("Failed to send tools/list_changed: {}", e);
+        }
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::json!({ "success": true, "selected": params.label }).to_string(),
+        )]))
+    }
+
+    // ----------------------------------------------------------------------
+    // get_console_logs — editor console output and uncaught JS errors
+    // ----------------------------------------------------------------------
+
+    #[tool(description = "Get recent console output and uncaught JavaScript errors of the editor window (the targeted one, or all windows), oldest first, with level, source URL, line and stack. Use level 'error' after an action to check whether it caused JS errors, and since (the timestamp of the last entry seen) to only get new entries.")]
+    async fn get_console_logs(
+        &self,
+        Parameters(params): Parameters<GetConsoleLogsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        use crate::console::{ConsoleLevel, ConsoleLog};
+
+        let level = params.level.unwrap_or(ConsoleLevel::Log);
+        let _tx = Self::start_tool_transaction("get_console_logs", &format!("{:?}", level));
+        let window = if params.all_windows.unwrap_or(false) {
+            None
+        } else {
+            Some(self.target_window().map_err(|e| McpError::invalid_request(e, None))?.label().to_string())
+        };
+        let entries = self.app_handle.state::<ConsoleLog>().recent(
+            window.as_deref(),
+            level,
+            params.since,
+            params.limit.unwrap_or(50),
+        );
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string(&entries).unwrap_or_default(),
+        )]))
+    }
+
+    // ----------------------------------------------------------------------
+    // get_app_state — what is open, loaded and running
+    // ----------------------------------------------------------------------
+
+    #[tool(description = "Get the state of Silex: target window and its URL, open website and unsaved changes, whether editor tools are loaded and how many, the current selection (breakpoint, page, component, selector) when the editor is live, server and MCP ports, and app and server versions. Use it to find out where you are before acting, or when a tool behaves unexpectedly.")]
+    async fn get_app_state(&self) -> Result<CallToolResult, McpError> {
+        let _tx = Self::start_tool_transaction("get_app_state", "get");
+        let window = self.target_window().ok();
+        let project = self.target_project();
+        let dynamic_tool_count = self.dynamic_tools().await.len();
+        let mcp_port = self
+            .app_handle
+            .try_state::<McpHttp>()
+            .and_then(|http| http.addr())
+            .map(|addr| addr.port());
+        // Only ask the editor when it's there, the dashboard has no selection
+        let selection = match project.website_id {
+            Some(_) => match self
+                .eval_js_internal("JSON.stringify(window.__silexMcp.getSelectionState(window.silex.getEditor()))", 5)
+                .await
+            {
+                Ok(Some(json)) => serde_json::from_str(&json).unwrap_or(serde_json::Value::Null),
+                Ok(None) => serde_json::Value::Null,
+                Err(e) => serde_json::json!({ "error": e }),
+            },
+            None => serde_json::Value::Null,
+        };
+        let state = serde_json::json!({
+            "window": window.as_ref().map(|w| w.label().to_string()),
+            "url": window.as_ref().and_then(|w| w.url().ok()).map(|u| u.to_string()),
+            "websiteId": project.website_id,
+            "websiteName": project.website_name,
+            "unsaved": project.has_unsaved_changes,
+            "capabilitiesLoaded": self.capabilities_loaded().await,
+            "dynamicToolCount": dynamic_tool_count,
+            "selection": selection,
+            "serverPort": self.app_handle.try_state::<crate::ServerInfo>().map(|info| info.port),
+            "mcpPort": mcp_port,
+            "mcpTransport": if self.app_handle.try_state::<McpHttp>().is_some() { "http" } else { "stdio" },
+            "versions": {
+                "app": env!("CARGO_PKG_VERSION"),
+                "server": silex_server::VERSION,
+                "tauri": tauri::VERSION,
+            },
+        });
+        Ok(CallToolResult::success(vec![Content::text(state.to_string())]))
+    }
 }
 
 // ==========================================================================
-// Eval callback handler
+// Eval callback and publish progress handlers
 // ==========================================================================
 
 pub async fn eval_callback(
@@ -690,6 +1299,17 @@ pub async fn eval_callback(
     "ok"
 }
 
+pub async fn publish_progress(
+    axum::extract::Extension(progress): axum::extract::Extension<PublishProgress>,
+    axum::extract::Path(id): axum::extract::Path<u64>,
+    body: String,
+) -> &'static str {
+    if let Some(tx) = progress.lock().unwrap().get(&id) {
+        let _ = tx.send(body);
+    }
+    "ok"
+}
+
 // ==========================================================================
 // ServerHandler — manual impl to merge static + dynamic tools
 // ==========================================================================
@@ -705,6 +1325,7 @@ impl ServerHandler for SilexMcp {
 
 GETTING STARTED:
 1. Call website(action:'list') or website(action:'create', name:'My Site') to start.
+   website(action:'templates') lists starters; pass template:'<id>' to create.
 2. After opening/creating a project, call list_tools to discover editor tools.
    Editor tools are loaded dynamically and won't appear until a project is open.
 
@@ -718,6 +1339,16 @@ RULES:
 - Homepage page name must be "index". Internal links start with "./".
 - Autosave is active — no manual save needed.
 - After making visual changes, use take_screenshot to verify your work.
+- Use publish(dry_run:true) to check the output files before publishing.
+
+WINDOWS:
+  Several editor windows can be open, each with its own project. Tools act
+  on the focused window; use list_windows and select_window to target another.
+
+ERRORS:
+  If an action doesn't do what you expected, get_app_state shows what is
+  open and selected, and get_console_logs(level:'error') the JavaScript
+  errors of the editor.
 "#
                 .into(),
             ),
@@ -727,14 +1358,16 @@ RULES:
     fn list_tools(
         &self,
         _request: Option<PaginatedRequestParams>,
-        _context: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<ListToolsResult, McpError>> + Send + '_ {
         async move {
+            self.register_peer(&context.peer);
+            let caps_loaded = self.capabilities_loaded().await;
             tracing::info!("[list_tools] Called. caps_loaded={} project_open={}",
-                self.capabilities_loaded.load(Ordering::Acquire),
+                caps_loaded,
                 self.require_project().is_ok());
             // Eagerly load capabilities if a project is open but caps haven't loaded yet
-            if !self.capabilities_loaded.load(Ordering::Acquire) && self.require_project().is_ok() {
+            if !caps_loaded && self.require_project().is_ok() {
                 tracing::info!("[list_tools] Loading capabilities eagerly...");
                 match self.load_capabilities().await {
                     Ok(n) => tracing::info!("[list_tools] Loaded {} capabilities", n),
@@ -745,7 +1378,7 @@ RULES:
             // Set annotations on static tools (macro doesn't support them)
             for tool in &mut tools {
                 let name = tool.name.as_ref();
-                if name == "take_screenshot" {
+                if matches!(name, "take_screenshot" | "list_windows" | "get_console_logs" | "get_app_state") {
                     tool.annotations = Some(ToolAnnotations {
                         read_only_hint: Some(true),
                         ..Default::default()
@@ -753,10 +1386,10 @@ RULES:
                 }
             }
             let static_count = tools.len();
-            // Append dynamic tools from capabilities
-            let dynamic = self.dynamic_tools.read().await;
-            let dynamic_count = dynamic.list_all().len();
-            tools.extend(dynamic.list_all());
+            // Append the target window's dynamic tools from capabilities
+            let dynamic = self.dynamic_tools().await;
+            let dynamic_count = dynamic.len();
+            tools.extend(dynamic);
             let tool_names: Vec<String> = tools.iter().map(|t| t.name.to_string()).collect();
             tracing::info!("[list_tools] Returning {} tools ({} static + {} dynamic): {:?}",
                 tools.len(), static_count, dynamic_count, tool_names);
@@ -770,47 +1403,25 @@ RULES:
         context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<CallToolResult, McpError>> + Send + '_ {
         async move {
-            // Check which router owns this tool before consuming request
-            if self.tool_router.get(&request.name).is_some() {
-                let is_website_tool = request.name.as_ref() == "website";
-                let caps_before = self.capabilities_loaded.load(Ordering::Acquire);
-                let peer = context.peer.clone();
-
-                let tool_ctx = rmcp::handler::server::tool::ToolCallContext::new(
-                    self, request, context,
-                );
-                let result = self.tool_router.call(tool_ctx).await;
-
-                // If the website tool just loaded capabilities, notify the client
-                if is_website_tool && !caps_before && self.capabilities_loaded.load(Ordering::Acquire) {
-                    if let Err(e) = peer.notify_tool_list_changed().await {
-                        tracing::warn!("Failed to send tools/list_changed: {}", e);
-                    }
-                }
-
-                return result;
+            self.register_peer(&context.peer);
+            let tool = request.name.to_string();
+            // Unknown names aren't counted, clients could send any
+            let known = self.get_tool(&tool).is_some();
+            let result = self.route_tool_call(request, context).await;
+            if known {
+                crate::metrics::record_tool_call(&tool, matches!(&result, Ok(r) if r.is_error != Some(true)));
             }
-
-            if self.dynamic_tools.read().await.has_route(&request.name) {
-                let tool_ctx = rmcp::handler::server::tool::ToolCallContext::new(
-                    self, request, context,
-                );
-                // Hold read lock across the async call — safe because writes
-                // only happen in load_capabilities() on a separate task.
-                let dynamic = self.dynamic_tools.read().await;
-                return dynamic.call(tool_ctx).await;
-            }
-
-            Err(McpError::invalid_params(
-                format!("Tool '{}' not found. Use list_tools to see available tools.", request.name),
-                None,
-            ))
+            result
         }
     }
 
     fn get_tool(&self, name: &str) -> Option<Tool> {
-        self.tool_router.get(name).cloned()
-            .or_else(|| self.dynamic_tools.try_read().ok()?.get(name).cloned())
+        self.tool_router.get(name).cloned().or_else(|| {
+            let window = self.target_window().ok()?;
+            let state = self.state();
+            let tools = state.window_tools.try_read().ok()?;
+            tools.get(window.label())?.get(name).cloned()
+        })
     }
 }
 
@@ -818,56 +1429,159 @@ RULES:
 // Server entry point
 // ==========================================================================
 
-pub async fn start_mcp_server(
-    app_handle: tauri::AppHandle,
+/// HTTP transport state: what's needed to (re)start it, and the running
+/// server if any. Managed by the app; the tray shows its status and
+/// toggles it on and off.
+pub struct McpHttp {
     pending_evals: PendingEvals,
+    publish_progress: PublishProgress,
     port: u16,
-) {
-    let eval_counter = Arc::new(AtomicU64::new(0));
-    // Shared across all sessions so dynamic tools persist after website open/create
-    let dynamic_tools = Arc::new(tokio::sync::RwLock::new(ToolRouter::new()));
-    let capabilities_loaded = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    running: std::sync::Mutex<Option<RunningServer>>,
+}
+
+struct RunningServer {
+    addr: std::net::SocketAddr,
+    sessions: Arc<LocalSessionManager>,
+    cancel: CancellationToken,
+}
+
+impl McpHttp {
+    pub fn new(pending_evals: PendingEvals, publish_progress: PublishProgress, port: u16) -> Self {
+        Self {
+            pending_evals,
+            publish_progress,
+            port,
+            running: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Listening address, None when stopped.
+    pub fn addr(&self) -> Option<std::net::SocketAddr> {
+        self.running.lock().unwrap().as_ref().map(|r| r.addr)
+    }
 
+    /// Endpoint URL agents connect to (the configured port when stopped).
+    pub fn url(&self) -> String {
+        let port = self.addr().map(|a| a.port()).unwrap_or(self.port);
+        format!("http://127.0.0.1:{}/mcp", port)
+    }
+
+    /// Number of connected MCP sessions.
+    pub async fn session_count(&self) -> usize {
+        let sessions = self.running.lock().unwrap().as_ref().map(|r| r.sessions.clone());
+        match sessions {
+            Some(sessions) => sessions.sessions.read().await.len(),
+            None => 0,
+        }
+    }
+
+    /// Close every session, then stop the server (app exit).
+    pub async fn shutdown(&self) {
+        let Some(running) = self.running.lock().unwrap().take() else { return };
+        running.close().await;
+    }
+
+    /// Stop the server, closing all sessions in the background. Returns
+    /// false if it wasn't running.
+    pub fn stop(&self) -> bool {
+        match self.running.lock().unwrap().take() {
+            Some(running) => {
+                tauri::async_runtime::spawn(running.close());
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl RunningServer {
+    /// Close the sessions, so clients see them end, then stop serving.
+    async fn close(self) {
+        let ids: Vec<_> = self.sessions.sessions.read().await.keys().cloned().collect();
+        for id in ids {
+            if let Err(e) = self.sessions.close_session(&id).await {
+                tracing::debug!("Failed to close MCP session {}: {}", id, e);
+            }
+        }
+        self.cancel.cancel();
+        tracing::info!("MCP server stopped");
+    }
+}
+
+/// Turn the HTTP transport on or off (tray toggle, settings window).
+pub fn set_enabled(app: &tauri::AppHandle, enabled: bool) {
+    let Some(http) = app.try_state::<McpHttp>() else { return };
+    if !enabled {
+        http.stop();
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if enabled {
+            if let Err(e) = start_mcp_server(app.clone()).await {
+                crate::menu::show_error(&app, "MCP Server", e);
+            }
+        }
+        crate::tray::refresh(&app).await;
+    });
+}
+
+/// Start the HTTP transport described by the managed `McpHttp`.
+pub async fn start_mcp_server(app_handle: tauri::AppHandle) -> Result<std::net::SocketAddr, String> {
+    let http = app_handle.state::<McpHttp>();
+    if let Some(addr) = http.addr() {
+        return Ok(addr);
+    }
+    let sessions = Arc::new(LocalSessionManager::default());
+    // Stopped with the app, or on its own from the tray and settings
+    let shutdown = app_handle.state::<crate::shutdown::Shutdown>();
+    let cancel = shutdown.token().child_token();
+
+    let (pending_evals, publish_progress) = (http.pending_evals.clone(), http.publish_progress.clone());
+    let factory_handle = app_handle.clone();
     let mcp_service = StreamableHttpService::new(
         move || {
             Ok(SilexMcp::new(
-                app_handle.clone(),
+                factory_handle.clone(),
                 pending_evals.clone(),
-                eval_counter.clone(),
-                dynamic_tools.clone(),
-                capabilities_loaded.clone(),
+                publish_progress.clone(),
             ))
         },
-        LocalSessionManager::default().into(),
-        Default::default(),
+        sessions.clone(),
+        StreamableHttpServerConfig {
+            cancellation_token: cancel.child_token(),
+            ..Default::default()
+        },
     );
 
     let router = axum::Router::new().nest_service("/mcp", mcp_service);
 
-    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
-    let listener = match tokio::net::TcpListener::bind(addr).await {
-        Ok(l) => l,
-        Err(e) => {
-            tracing::warn!("MCP HTTP port {} unavailable ({}), skipping", port, e);
-            return;
-        }
-    };
-    let addr = listener.local_addr().unwrap();
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], http.port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("MCP HTTP port {} unavailable: {}", http.port, e))?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?;
     tracing::info!("MCP server listening on http://{}/mcp", addr);
 
-    tokio::spawn(async move {
-        axum::serve(listener, router).await.unwrap();
-    });
+    let stop = cancel.clone();
+    shutdown.track(tauri::async_runtime::spawn(async move {
+        if let Err(e) = axum::serve(listener, router)
+            .with_graceful_shutdown(stop.cancelled_owned())
+            .await
+        {
+            tracing::error!("MCP server error: {}", e);
+        }
+    }));
+
+    *http.running.lock().unwrap() = Some(RunningServer { addr, sessions, cancel });
+    Ok(addr)
 }
 
 pub async fn start_mcp_stdio(
     app_handle: tauri::AppHandle,
     pending_evals: PendingEvals,
+    publish_progress: PublishProgress,
 ) {
-    let eval_counter = Arc::new(AtomicU64::new(0));
-    let dynamic_tools = Arc::new(tokio::sync::RwLock::new(ToolRouter::new()));
-    let capabilities_loaded = Arc::new(std::sync::atomic::AtomicBool::new(false));
-    let service = SilexMcp::new(app_handle, pending_evals, eval_counter, dynamic_tools, capabilities_loaded);
+    let service = SilexMcp::new(app_handle, pending_evals, publish_progress);
     tracing::info!("MCP stdio transport starting");
     match service.serve(rmcp::transport::io::stdio()).await {
         Ok(server) => {
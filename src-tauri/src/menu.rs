@@ -0,0 +1,399 @@
+/*
+ * Native application menu.
+ *
+ * Items either emit the events the desktop bridge already listens for
+ * (menu-save, menu-close-project) or act on the Rust side (new website,
+ * import/export, zoom, devtools, preview). Everything targets the focused
+ * editor window; items that need an open project are enabled from that
+ * window's project state, see `refresh`. Undo and redo are the native
+ * items, so the keys reach the focused field or the editor's own keymaps.
+ */
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tauri::menu::{
+    AboutMetadata, CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu,
+    HELP_SUBMENU_ID,
+};
+use tauri::{Emitter, Manager, Wry};
+
+use crate::{recent, AppState};
+
+#[cfg(target_os = "macos")]
+const DEVTOOLS: &str = "Alt+Cmd+I";
+#[cfg(not(target_os = "macos"))]
+const DEVTOOLS: &str = "Ctrl+Shift+I";
+
+const ZOOM_STEP: f64 = 0.1;
+
+/// Menu items toggled at runtime.
+pub struct MenuState {
+    /// Items that only make sense with a project open.
+    project_items: Vec<MenuItem<Wry>>,
+    pub open_recent: Submenu<Wry>,
+    reopen_last_project: CheckMenuItem<Wry>,
+    /// Zoom factor of each editor window, by label.
+    zoom: Mutex<HashMap<String, f64>>,
+}
+
+fn item(app: &tauri::AppHandle, id: &str, text: &str, accelerator: Option<&str>) -> tauri::Result<MenuItem<Wry>> {
+    MenuItem::with_id(app, id, text, true, accelerator)
+}
+
+/// Build the application menu and register its state.
+pub fn build(app: &tauri::AppHandle) -> tauri::Result<Menu<Wry>> {
+    let save = item(app, "save", "Save", Some("CmdOrCtrl+S"))?;
+    let close_project = item(app, "close-project", "Close Project", Some("CmdOrCtrl+W"))?;
+    let export = item(app, "export", "Export Website\u{2026}", Some("CmdOrCtrl+Shift+E"))?;
+    let preview = item(app, "preview-published", "Preview Published Site", Some("CmdOrCtrl+Shift+P"))?;
+    let preview_lan = item(app, "preview-lan", "Preview on Phone (LAN)\u{2026}", None)?;
+    let quit = item(app, "quit", "Quit Silex", Some("CmdOrCtrl+Q"))?;
+    let settings = item(app, "settings", "Settings\u{2026}", Some("CmdOrCtrl+,"))?;
+
+    let open_recent = Submenu::new(app, "Open Recent", true)?;
+    fill_recent(app, &open_recent)?;
+    let reopen_last_project = CheckMenuItem::with_id(
+        app,
+        "reopen-last-project",
+        "Reopen Last Project at Launch",
+        true,
+        crate::settings::get(app).reopen_last_project,
+        None::<&str>,
+    )?;
+
+    let about = PredefinedMenuItem::about(
+        app,
+        Some("About Silex"),
+        Some(AboutMetadata {
+            name: Some("Silex".into()),
+            version: Some(env!("CARGO_PKG_VERSION").into()),
+            comments: Some(env!("CARGO_PKG_DESCRIPTION").into()),
+            license: Some(env!("CARGO_PKG_LICENSE").into()),
+            website: Some("https://www.silex.me".into()),
+            ..Default::default()
+        }),
+    )?;
+    let separator = || PredefinedMenuItem::separator(app);
+
+    let mut file_items: Vec<Box<dyn tauri::menu::IsMenuItem<Wry>>> = vec![
+        Box::new(item(app, "new", "New Website", Some("CmdOrCtrl+N"))?),
+        Box::new(item(app, "new-window", "New Window", Some("CmdOrCtrl+Shift+N"))?),
+        Box::new(open_recent.clone()),
+        Box::new(reopen_last_project.clone()),
+        Box::new(separator()?),
+        Box::new(save.clone()),
+        Box::new(close_project.clone()),
+        Box::new(separator()?),
+        Box::new(item(app, "import", "Import Website\u{2026}", Some("CmdOrCtrl+Shift+O"))?),
+        Box::new(export.clone()),
+    ];
+    // On macOS, Settings and Quit live in the application menu
+    if !cfg!(target_os = "macos") {
+        file_items.push(Box::new(separator()?));
+        file_items.push(Box::new(settings.clone()));
+        file_items.push(Box::new(separator()?));
+        file_items.push(Box::new(quit.clone()));
+    }
+    let file_refs: Vec<&dyn tauri::menu::IsMenuItem<Wry>> = file_items.iter().map(|i| i.as_ref()).collect();
+    let file = Submenu::with_items(app, "File", true, &file_refs)?;
+
+    let edit = Submenu::with_items(
+        app,
+        "Edit",
+        true,
+        &[
+            &PredefinedMenuItem::undo(app, None)?,
+            &PredefinedMenuItem::redo(app, None)?,
+            &separator()?,
+            &PredefinedMenuItem::cut(app, None)?,
+            &PredefinedMenuItem::copy(app, None)?,
+            &PredefinedMenuItem::paste(app, None)?,
+            &PredefinedMenuItem::select_all(app, None)?,
+        ],
+    )?;
+
+    let view = Submenu::with_items(
+        app,
+        "View",
+        true,
+        &[
+            &item(app, "zoom-in", "Zoom In", Some("CmdOrCtrl+="))?,
+            &item(app, "zoom-out", "Zoom Out", Some("CmdOrCtrl+-"))?,
+            &item(app, "zoom-reset", "Actual Size", Some("CmdOrCtrl+0"))?,
+            &separator()?,
+            &preview,
+            &preview_lan,
+            &separator()?,
+            &item(app, "devtools", "Toggle Developer Tools", Some(DEVTOOLS))?,
+        ],
+    )?;
+
+    let logs = item(app, "logs", "Show Logs", None)?;
+    let help = if cfg!(target_os = "macos") {
+        Submenu::with_id_and_items(app, HELP_SUBMENU_ID, "Help", true, &[&logs])?
+    } else {
+        Submenu::with_id_and_items(app, HELP_SUBMENU_ID, "Help", true, &[&logs, &separator()?, &about])?
+    };
+
+    let menu = Menu::new(app)?;
+    #[cfg(target_os = "macos")]
+    menu.append(&Submenu::with_items(
+        app,
+        "Silex",
+        true,
+        &[
+            &about,
+            &separator()?,
+            &settings,
+            &separator()?,
+            &PredefinedMenuItem::services(app, None)?,
+            &separator()?,
+            &PredefinedMenuItem::hide(app, None)?,
+            &PredefinedMenuItem::hide_others(app, None)?,
+            &PredefinedMenuItem::show_all(app, None)?,
+            &separator()?,
+            &quit,
+        ],
+    )?)?;
+    menu.append_items(&[&file, &edit, &view, &help])?;
+
+    app.manage(MenuState {
+        project_items: vec![save, close_project, export, preview, preview_lan],
+        open_recent,
+        reopen_last_project,
+        zoom: Mutex::new(HashMap::new()),
+    });
+    refresh(app);
+    Ok(menu)
+}
+
+/// Sync the check items with settings changed in the settings window.
+pub fn apply_settings(app: &tauri::AppHandle, settings: &crate::settings::Settings) {
+    if let Some(menu) = app.try_state::<MenuState>() {
+        let _ = menu.reopen_last_project.set_checked(settings.reopen_last_project);
+    }
+}
+
+/// Enable or disable project items from the focused window's project.
+pub fn refresh(app: &tauri::AppHandle) {
+    let Some(menu) = app.try_state::<MenuState>() else { return };
+    let project_open = crate::focused_editor(app)
+        .is_some_and(|w| app.state::<AppState>().project(w.label()).website_id.is_some());
+    for item in &menu.project_items {
+        let _ = item.set_enabled(project_open);
+    }
+}
+
+pub(crate) fn fill_recent(app: &tauri::AppHandle, submenu: &Submenu<Wry>) -> tauri::Result<()> {
+    for existing in submenu.items()? {
+        submenu.remove(&existing)?;
+    }
+    let projects = recent::list(app);
+    if projects.is_empty() {
+        submenu.append(&MenuItem::with_id(app, "recent-none", "No Recent Projects", false, None::<&str>)?)?;
+        return Ok(());
+    }
+    for project in projects {
+        submenu.append(&MenuItem::with_id(
+            app,
+            format!("recent:{}", project.website_id),
+            &project.name,
+            true,
+            None::<&str>,
+        )?)?;
+    }
+    submenu.append(&PredefinedMenuItem::separator(app)?)?;
+    submenu.append(&item(app, "recent-clear", "Clear Recent Projects", None)?)?;
+    Ok(())
+}
+
+/// Rebuild File > Open Recent (and the tray's list) from the persisted list.
+pub fn refresh_recent(app: &tauri::AppHandle) {
+    crate::tray::refresh_recent(app);
+    let Some(menu) = app.try_state::<MenuState>() else { return };
+    if let Err(e) = fill_recent(app, &menu.open_recent) {
+        tracing::warn!("Failed to update recent projects menu: {}", e);
+    }
+}
+
+fn open_recent(app: &tauri::AppHandle, website_id: &str) {
+    if let Err(e) = crate::launch::open_website(app, website_id) {
+        show_error(app, "Open Recent", e);
+    }
+}
+
+/// Send a menu event to the focused editor window only.
+fn emit_to_editor(app: &tauri::AppHandle, event: &str) {
+    if let Some(window) = crate::focused_editor(app) {
+        let _ = app.emit_to(window.label(), event, ());
+    }
+}
+
+/// Back to the dashboard, asking first if there are unsaved changes. Only
+/// when an editor window is in front: with Settings or Logs focused, the
+/// shortcut must not close the project behind them.
+fn close_project(app: &tauri::AppHandle) {
+    let Some(window) = crate::focused_editor(app).filter(|w| w.is_focused().unwrap_or(false)) else { return };
+    let project = app.state::<AppState>().project(window.label());
+    if project.website_id.is_none() {
+        return;
+    }
+    if project.has_unsaved_changes {
+        crate::close::confirm_close(&window, project.website_name.as_deref(), crate::close::Closing::Project);
+    } else {
+        let _ = window.emit_to(window.label(), "menu-close-project", ());
+    }
+}
+
+fn set_zoom(app: &tauri::AppHandle, change: impl FnOnce(f64) -> f64) {
+    let Some(window) = crate::focused_editor(app) else { return };
+    let state = app.state::<MenuState>();
+    let mut zooms = state.zoom.lock().unwrap();
+    let zoom = zooms.entry(window.label().to_string()).or_insert(1.0);
+    *zoom = change(*zoom).clamp(0.3, 3.0);
+    let _ = window.set_zoom(*zoom);
+}
+
+fn toggle_devtools(app: &tauri::AppHandle) {
+    if let Some(window) = crate::focused_editor(app) {
+        if window.is_devtools_open() {
+            window.close_devtools();
+        } else {
+            window.open_devtools();
+        }
+    }
+}
+
+pub fn show_error(app: &tauri::AppHandle, title: &str, message: String) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+    app.dialog()
+        .message(message)
+        .title(title)
+        .kind(MessageDialogKind::Error)
+        .show(|_| {});
+}
+
+fn new_website(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = crate::templates::create_from_template(&app, &crate::base_url(&app), "blank", None).await;
+        let result = result.and_then(|id| crate::navigate_to(&app, &format!("{}/?id={}", crate::base_url(&app), id)));
+        if let Err(e) = result {
+            show_error(&app, "New Website", e);
+        }
+    });
+}
+
+fn import_website(app: &tauri::AppHandle) {
+    use tauri_plugin_dialog::DialogExt;
+
+    let app_handle = app.clone();
+    app.dialog()
+        .file()
+        .set_title("Import Website")
+        .add_filter("Silex website archive", &["zip"])
+        .pick_file(move |path| {
+            let Some(path) = path.and_then(|p| p.into_path().ok()) else { return };
+            tauri::async_runtime::spawn(async move {
+                let base_url = crate::base_url(&app_handle);
+                let result = crate::archive::import_website(&app_handle, &base_url, &path).await;
+                let result = result.and_then(|id| crate::navigate_to(&app_handle, &format!("{}/?id={}", base_url, id)));
+                if let Err(e) = result {
+                    show_error(&app_handle, "Import Website", e);
+                }
+            });
+        });
+}
+
+fn export_website(app: &tauri::AppHandle) {
+    use tauri_plugin_dialog::DialogExt;
+
+    let Some(window) = crate::focused_editor(app) else { return };
+    let project = app.state::<AppState>().project(window.label());
+    let Some(website_id) = project.website_id else { return };
+    let name = project.website_name.unwrap_or_else(|| website_id.clone());
+    let unsaved = project.has_unsaved_changes;
+
+    let app_handle = app.clone();
+    app.dialog()
+        .file()
+        .set_title("Export Website")
+        .set_file_name(format!("{}.zip", name))
+        .add_filter("Silex website archive", &["zip"])
+        .save_file(move |path| {
+            let Some(path) = path.and_then(|p| p.into_path().ok()) else { return };
+            tauri::async_runtime::spawn(async move {
+                // Export what's on disk, so save pending changes first
+                let saved = if unsaved {
+                    crate::close::save(&window).await.map_err(|e| format!("The website could not be saved: {}", e))
+                } else {
+                    Ok(())
+                };
+                let result = saved.and_then(|()| crate::archive::export_website(&app_handle, &website_id, &path));
+                if let Err(e) = result {
+                    show_error(&app_handle, "Export Website", e);
+                }
+            });
+        });
+}
+
+fn new_window(app: &tauri::AppHandle) {
+    if let Err(e) = crate::open_editor_window(app, None) {
+        show_error(app, "New Window", e);
+    }
+}
+
+pub(crate) fn quit(app: &tauri::AppHandle) {
+    // Closing editor windows goes through the unsaved-changes check;
+    // the app exits once the last one is gone, even in tray mode
+    crate::tray::set_quitting(app);
+    let windows = crate::editor_windows(app);
+    if windows.is_empty() {
+        app.exit(0);
+    }
+    for window in windows {
+        let _ = window.close();
+    }
+}
+
+pub fn on_menu_event(app: &tauri::AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        "new" => new_website(app),
+        "new-window" => new_window(app),
+        "save" => emit_to_editor(app, "menu-save"),
+        "close-project" => close_project(app),
+        "import" => import_website(app),
+        "export" => export_website(app),
+        "quit" => quit(app),
+        "zoom-in" => set_zoom(app, |z| z + ZOOM_STEP),
+        "zoom-out" => set_zoom(app, |z| z - ZOOM_STEP),
+        "zoom-reset" => set_zoom(app, |_| 1.0),
+        "devtools" => toggle_devtools(app),
+        "preview-published" => crate::open_preview(app),
+        "preview-lan" => crate::confirm_lan_preview(app),
+        "logs" => crate::logs::show_window(app),
+        "settings" => crate::settings::show_window(app),
+        "reopen-last-project" => {
+            // The item toggles itself, persist its new state
+            let enabled = app
+                .state::<MenuState>()
+                .reopen_last_project
+                .is_checked()
+                .unwrap_or(false);
+            crate::settings::update(app, |s| s.reopen_last_project = enabled);
+        }
+        "recent-clear" => {
+            recent::clear(app);
+            refresh_recent(app);
+        }
+        id => {
+            if let Some(website_id) = id.strip_prefix("recent:") {
+                open_recent(app, website_id);
+            } else if id.starts_with("tray-") {
+                crate::tray::on_menu_event(app, id);
+            }
+        }
+    }
+}
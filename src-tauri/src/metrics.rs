@@ -0,0 +1,214 @@
+/*
+ * Health and metrics of the embedded server.
+ *
+ *   /health    JSON status (version, uptime, restarts, pending evals),
+ *              answered as long as the server task is alive
+ *   /metrics   Prometheus text format: HTTP requests and latencies, request
+ *              handler panics, eval round-trip times and timeouts, pending
+ *              evals, MCP sessions, MCP tool calls and errors, server restarts
+ *
+ * Counters live in a process-wide registry, fed by the request middleware
+ * (`track_request`), the panic handler, the eval bridge, MCP tool calls and
+ * the server supervisor.
+ */
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use axum::extract::{Extension, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use tauri::Manager;
+
+use crate::mcp::{McpHttp, PendingEvals};
+
+/// Histogram buckets, in seconds.
+const BUCKETS: [f64; 12] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+#[derive(Default)]
+struct Histogram {
+    /// Observations per bucket (not cumulative).
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let secs = duration.as_secs_f64();
+        if let Some(index) = BUCKETS.iter().position(|&b| secs <= b) {
+            self.buckets[index] += 1;
+        }
+        self.count += 1;
+        self.sum += secs;
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {} {}\n# TYPE {} histogram", name, help, name);
+        let mut cumulative = 0;
+        for (bound, count) in BUCKETS.iter().zip(self.buckets) {
+            cumulative += count;
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, self.count);
+        let _ = writeln!(out, "{}_sum {}\n{}_count {}", name, self.sum, name, self.count);
+    }
+}
+
+struct Metrics {
+    started: Instant,
+    /// Requests by method and status code.
+    requests: Mutex<BTreeMap<(String, u16), u64>>,
+    request_duration: Mutex<Histogram>,
+    handler_panics: AtomicU64,
+    eval_duration: Mutex<Histogram>,
+    eval_timeouts: AtomicU64,
+    /// Calls and errors by tool name.
+    tool_calls: Mutex<BTreeMap<String, (u64, u64)>>,
+    server_restarts: AtomicU64,
+}
+
+fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics {
+        started: Instant::now(),
+        requests: Default::default(),
+        request_duration: Default::default(),
+        handler_panics: Default::default(),
+        eval_duration: Default::default(),
+        eval_timeouts: Default::default(),
+        tool_calls: Default::default(),
+        server_restarts: Default::default(),
+    })
+}
+
+/// Middleware counting requests and their latency.
+pub async fn track_request(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let started = Instant::now();
+    let response = next.run(request).await;
+    let m = metrics();
+    *m.requests.lock().unwrap().entry((method, response.status().as_u16())).or_default() += 1;
+    m.request_duration.lock().unwrap().observe(started.elapsed());
+    response
+}
+
+/// A request handler panicked (answered with a 500, the server keeps running).
+pub fn record_handler_panic() {
+    metrics().handler_panics.fetch_add(1, Ordering::Relaxed);
+}
+
+/// An eval_js round trip: injected, then answered through /eval-callback.
+pub fn record_eval(duration: Duration) {
+    metrics().eval_duration.lock().unwrap().observe(duration);
+}
+
+pub fn record_eval_timeout() {
+    metrics().eval_timeouts.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_tool_call(tool: &str, success: bool) {
+    let mut calls = metrics().tool_calls.lock().unwrap();
+    let (count, errors) = calls.entry(tool.to_string()).or_default();
+    *count += 1;
+    if !success {
+        *errors += 1;
+    }
+}
+
+pub fn record_server_restart() {
+    metrics().server_restarts.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Escape a Prometheus label value.
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+async fn mcp_sessions(app: &tauri::AppHandle) -> Option<usize> {
+    match app.try_state::<McpHttp>() {
+        Some(http) => Some(http.session_count().await),
+        None => None,
+    }
+}
+
+pub async fn health(
+    Extension(pending): Extension<PendingEvals>,
+) -> axum::Json<serde_json::Value> {
+    let m = metrics();
+    axum::Json(serde_json::json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+        "uptimeSeconds": m.started.elapsed().as_secs(),
+        "restarts": m.server_restarts.load(Ordering::Relaxed),
+        "pendingEvals": pending.lock().unwrap().len(),
+    }))
+}
+
+pub async fn render(
+    Extension(app): Extension<tauri::AppHandle>,
+    Extension(pending): Extension<PendingEvals>,
+) -> impl IntoResponse {
+    let m = metrics();
+    let mut out = String::new();
+    let gauge = |out: &mut String, name: &str, help: &str, kind: &str, value: String| {
+        let _ = writeln!(out, "# HELP {} {}\n# TYPE {} {}\n{} {}", name, help, name, kind, name, value);
+    };
+
+    gauge(&mut out, "silex_uptime_seconds", "Seconds since the app started.", "gauge", m.started.elapsed().as_secs().to_string());
+    gauge(
+        &mut out,
+        "silex_server_restarts_total",
+        "Restarts of the server task after a failure.",
+        "counter",
+        m.server_restarts.load(Ordering::Relaxed).to_string(),
+    );
+
+    let _ = writeln!(out, "# HELP silex_http_requests_total HTTP requests by method and status.\n# TYPE silex_http_requests_total counter");
+    for ((method, status), count) in m.requests.lock().unwrap().iter() {
+        let _ = writeln!(out, "silex_http_requests_total{{method=\"{}\",status=\"{}\"}} {}", label(method), status, count);
+    }
+    m.request_duration
+        .lock()
+        .unwrap()
+        .render(&mut out, "silex_http_request_duration_seconds", "HTTP request latency.");
+    gauge(
+        &mut out,
+        "silex_http_handler_panics_total",
+        "Requests whose handler panicked.",
+        "counter",
+        m.handler_panics.load(Ordering::Relaxed).to_string(),
+    );
+
+    m.eval_duration
+        .lock()
+        .unwrap()
+        .render(&mut out, "silex_eval_duration_seconds", "Round trip of JS evaluated in the editor.");
+    gauge(
+        &mut out,
+        "silex_eval_timeouts_total",
+        "JS evaluations without an answer in time.",
+        "counter",
+        m.eval_timeouts.load(Ordering::Relaxed).to_string(),
+    );
+    let pending_count = pending.lock().unwrap().len();
+    gauge(&mut out, "silex_pending_evals", "JS evaluations waiting for an answer.", "gauge", pending_count.to_string());
+
+    if let Some(sessions) = mcp_sessions(&app).await {
+        gauge(&mut out, "silex_mcp_sessions", "Connected MCP HTTP sessions.", "gauge", sessions.to_string());
+    }
+    let calls = m.tool_calls.lock().unwrap().clone();
+    let _ = writeln!(out, "# HELP silex_mcp_tool_calls_total MCP tool calls by tool.\n# TYPE silex_mcp_tool_calls_total counter");
+    for (tool, (count, _)) in &calls {
+        let _ = writeln!(out, "silex_mcp_tool_calls_total{{tool=\"{}\"}} {}", label(tool), count);
+    }
+    let _ = writeln!(out, "# HELP silex_mcp_tool_errors_total Failed MCP tool calls by tool.\n# TYPE silex_mcp_tool_errors_total counter");
+    for (tool, (_, errors)) in &calls {
+        let _ = writeln!(out, "silex_mcp_tool_errors_total{{tool=\"{}\"}} {}", label(tool), errors);
+    }
+
+    ([(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")], out)
+}
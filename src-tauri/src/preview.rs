@@ -0,0 +1,414 @@
+/*
+ * Local preview server for published websites.
+ *
+ * Serves a website's published output (the local folder written by the
+ * fs-hosting connector) on its own localhost port, with clean URLs
+ * (/about -> about.html or about/index.html) and optional live reload:
+ * HTML pages get a small script that reloads when the site is republished.
+ *
+ * LAN sharing is opt-in: it exposes one preview (never the editor or MCP)
+ * on the LAN interface behind a random access token, and stops by itself
+ * after a timeout.
+ */
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderValue, StatusCode, Uri};
+use axum::middleware::Next;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use tauri::Manager;
+use tokio::sync::{broadcast, oneshot};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+
+const EVENTS_PATH: &str = "/__silex_preview/events";
+
+const TOKEN_COOKIE: &str = "silex_preview_token";
+
+/// Limits of a LAN share's duration, in minutes.
+pub const LAN_MIN_MINUTES: u64 = 1;
+pub const LAN_MAX_MINUTES: u64 = 24 * 60;
+
+const LIVE_RELOAD_SCRIPT: &str = r#"<script>new EventSource('/__silex_preview/events').addEventListener('reload',()=>location.reload())</script>"#;
+
+// ==================
+// State
+// ==================
+
+/// What a preview server serves. Shared by the axum handlers.
+struct PreviewSite {
+    root: PathBuf,
+    live_reload: bool,
+    reload: broadcast::Sender<()>,
+}
+
+struct PreviewServer {
+    port: u16,
+    site: Arc<PreviewSite>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+/// The preview currently shared on the LAN (at most one).
+struct LanShare {
+    website_id: String,
+    /// Site of the local preview when sharing started (kept if that one restarts).
+    site: Arc<PreviewSite>,
+    info: LanPreview,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanPreview {
+    /// URL including the access token, for phones on the same network.
+    pub url: String,
+    /// Unix timestamp (seconds) when sharing stops.
+    pub expires_at: u64,
+    /// QR code of the URL, as an SVG document.
+    pub qr_svg: String,
+}
+
+/// Running preview servers, by website id, and the LAN share if any.
+#[derive(Default)]
+pub struct PreviewServers {
+    servers: Mutex<HashMap<String, PreviewServer>>,
+    lan: Mutex<Option<LanShare>>,
+}
+
+/// Folder where the fs-hosting connector publishes a website.
+pub fn published_dir(app: &tauri::AppHandle, website_id: &str) -> Result<PathBuf, String> {
+    crate::websites::validate_website_id(website_id)?;
+    Ok(std::env::var_os("SILEX_FS_HOSTING_ROOT")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            app.path()
+                .app_data_dir()
+                .unwrap_or_else(|_| PathBuf::from("."))
+                .join("hosting")
+        })
+        .join(website_id))
+}
+
+// ==================
+// Start / stop / reload
+// ==================
+
+/// Start (or reuse) the preview server for a website. Returns its URL.
+/// A server running with the other live reload setting is restarted.
+pub async fn start_preview(
+    app: &tauri::AppHandle,
+    website_id: &str,
+    live_reload: bool,
+) -> Result<String, String> {
+    let root = published_dir(app, website_id)?;
+    let state = app.state::<PreviewServers>();
+    let running = state.servers.lock().unwrap().get(website_id).map(|s| (s.port, s.site.live_reload));
+    match running {
+        Some((port, running_live_reload)) if running_live_reload == live_reload => {
+            return Ok(format!("http://localhost:{}/", port));
+        }
+        Some(_) => {
+            stop_preview(app, website_id);
+        }
+        None => {}
+    }
+
+    if !root.is_dir() {
+        return Err(format!(
+            "Website '{}' has not been published to a local folder yet ({})",
+            website_id,
+            root.display()
+        ));
+    }
+
+    let (reload, _) = broadcast::channel(4);
+    let site = Arc::new(PreviewSite { root, live_reload, reload });
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .await
+        .map_err(|e| format!("Failed to start preview server: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to start preview server: {}", e))?
+        .port();
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let router = router(site.clone());
+    tokio::spawn(async move {
+        let server = axum::serve(listener, router).with_graceful_shutdown(async {
+            let _ = shutdown_rx.await;
+        });
+        if let Err(e) = server.await {
+            tracing::warn!("Preview server error: {}", e);
+        }
+    });
+
+    tracing::info!("Preview of {} listening on http://localhost:{}/", website_id, port);
+    state.servers.lock().unwrap().insert(
+        website_id.to_string(),
+        PreviewServer { port, site, shutdown: Some(shutdown_tx) },
+    );
+    Ok(format!("http://localhost:{}/", port))
+}
+
+pub fn stop_preview(app: &tauri::AppHandle, website_id: &str) -> bool {
+    let state = app.state::<PreviewServers>();
+    let server = state.servers.lock().unwrap().remove(website_id);
+    match server {
+        Some(mut server) => {
+            if let Some(tx) = server.shutdown.take() {
+                let _ = tx.send(());
+            }
+            tracing::info!("Preview of {} stopped", website_id);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Share a website's preview on the LAN for `minutes` (clamped to
+/// LAN_MIN_MINUTES..=LAN_MAX_MINUTES), behind a random token. Replaces any
+/// previous LAN share, keeping its QR code window.
+pub async fn start_lan_preview(
+    app: &tauri::AppHandle,
+    website_id: &str,
+    minutes: u64,
+) -> Result<LanPreview, String> {
+    let minutes = minutes.clamp(LAN_MIN_MINUTES, LAN_MAX_MINUTES);
+    // Reuse the local preview's site (root, live reload channel), as it is
+    let state = app.state::<PreviewServers>();
+    let live_reload = state.servers.lock().unwrap().get(website_id).map(|s| s.site.live_reload);
+    start_preview(app, website_id, live_reload.unwrap_or(true)).await?;
+    let site = state
+        .servers
+        .lock()
+        .unwrap()
+        .get(website_id)
+        .map(|s| s.site.clone())
+        .ok_or("Preview server stopped unexpectedly")?;
+    stop_lan_share(&state);
+
+    let ip = local_ip_address::local_ip()
+        .map_err(|e| format!("No LAN interface found: {}", e))?;
+    let listener = tokio::net::TcpListener::bind(SocketAddr::new(ip, 0))
+        .await
+        .map_err(|e| format!("Failed to share preview on {}: {}", ip, e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to share preview: {}", e))?
+        .port();
+
+    let token = Arc::new(format!("{:032x}", rand::random::<u128>()));
+    let url = format!("http://{}/?token={}", SocketAddr::new(ip, port), token);
+    let qr_svg = qrcode::QrCode::new(url.as_bytes())
+        .map_err(|e| format!("Failed to generate QR code: {}", e))?
+        .render::<qrcode::render::svg::Color>()
+        .min_dimensions(240, 240)
+        .build();
+    let expires_at = crate::unix_time().as_secs() + minutes * 60;
+    let info = LanPreview { url, expires_at, qr_svg };
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let router = router(site.clone()).layer(axum::middleware::from_fn_with_state(token, require_token));
+    tokio::spawn(async move {
+        let server = axum::serve(listener, router).with_graceful_shutdown(async {
+            let _ = shutdown_rx.await;
+        });
+        if let Err(e) = server.await {
+            tracing::warn!("LAN preview server error: {}", e);
+        }
+    });
+
+    // Auto-expire, unless another share replaced this one in the meantime
+    let expiring = app.clone();
+    let expires_at = info.expires_at;
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(minutes * 60)).await;
+        let state = expiring.state::<PreviewServers>();
+        let current = state.lan.lock().unwrap().as_ref().map(|l| l.info.expires_at);
+        if current == Some(expires_at) {
+            tracing::info!("LAN preview expired");
+            stop_lan_preview(&expiring);
+        }
+    });
+
+    tracing::info!(
+        "Preview of {} shared on the LAN at {}:{} for {} min",
+        website_id, ip, port, minutes
+    );
+    *state.lan.lock().unwrap() = Some(LanShare {
+        website_id: website_id.to_string(),
+        site,
+        info: info.clone(),
+        shutdown: Some(shutdown_tx),
+    });
+    Ok(info)
+}
+
+/// Stop LAN sharing and close the QR code window. Returns false if not sharing.
+pub fn stop_lan_preview(app: &tauri::AppHandle) -> bool {
+    if let Some(window) = app.get_webview_window("lan-preview") {
+        let _ = window.close();
+    }
+    stop_lan_share(&app.state::<PreviewServers>())
+}
+
+fn stop_lan_share(state: &PreviewServers) -> bool {
+    let share = state.lan.lock().unwrap().take();
+    match share {
+        Some(mut share) => {
+            if let Some(tx) = share.shutdown.take() {
+                let _ = tx.send(());
+            }
+            tracing::info!("LAN preview of {} stopped", share.website_id);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn lan_preview(app: &tauri::AppHandle) -> Option<LanPreview> {
+    let state = app.state::<PreviewServers>();
+    let share = state.lan.lock().unwrap();
+    share.as_ref().map(|s| s.info.clone())
+}
+
+/// Tell open preview pages of a website to reload (after a republish).
+pub fn notify_published(app: &tauri::AppHandle, website_id: &str) {
+    let state = app.state::<PreviewServers>();
+    let local = state.servers.lock().unwrap().get(website_id).map(|s| s.site.clone());
+    let shared = state
+        .lan
+        .lock()
+        .unwrap()
+        .as_ref()
+        .filter(|share| share.website_id == website_id)
+        .map(|share| share.site.clone());
+    if let Some(site) = &local {
+        let _ = site.reload.send(());
+    }
+    // The LAN share keeps the site of a local preview restarted since
+    if let Some(site) = shared.filter(|shared| !local.as_ref().is_some_and(|local| Arc::ptr_eq(local, shared))) {
+        let _ = site.reload.send(());
+    }
+}
+
+// ==================
+// HTTP
+// ==================
+
+fn router(site: Arc<PreviewSite>) -> axum::Router {
+    axum::Router::new()
+        .route(EVENTS_PATH, axum::routing::get(reload_events))
+        .fallback(serve_file)
+        .with_state(site)
+}
+
+async fn reload_events(
+    State(site): State<Arc<PreviewSite>>,
+) -> Sse<impl tokio_stream::Stream<Item = Result<Event, std::convert::Infallible>>> {
+    let stream = BroadcastStream::new(site.reload.subscribe())
+        .filter_map(|msg| msg.ok().map(|_| Ok(Event::default().event("reload").data(""))));
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// LAN access control: the token comes in the URL on the first request,
+/// then in a cookie so relative links and assets keep working.
+async fn require_token(State(token): State<Arc<String>>, req: Request, next: Next) -> Response {
+    let in_query = req
+        .uri()
+        .query()
+        .map(|q| {
+            url::form_urlencoded::parse(q.as_bytes())
+                .any(|(k, v)| k == "token" && v == token.as_str())
+        })
+        .unwrap_or(false);
+    let expected_cookie = format!("{}={}", TOKEN_COOKIE, token);
+    let in_cookie = req
+        .headers()
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .any(|c| c.trim() == expected_cookie);
+    if !in_query && !in_cookie {
+        return (StatusCode::FORBIDDEN, "Invalid or expired preview link").into_response();
+    }
+
+    let mut response = next.run(req).await;
+    if in_query {
+        if let Ok(cookie) = HeaderValue::from_str(&format!(
+            "{}; Path=/; HttpOnly; SameSite=Strict",
+            expected_cookie
+        )) {
+            response.headers_mut().insert(header::SET_COOKIE, cookie);
+        }
+    }
+    response
+}
+
+/// Map a URL path to a file under root, refusing anything that escapes it.
+fn resolve(root: &Path, url_path: &str) -> Option<PathBuf> {
+    let decoded = percent_encoding::percent_decode_str(url_path.trim_start_matches('/'))
+        .decode_utf8_lossy()
+        .into_owned();
+    let relative = Path::new(&decoded);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+    let base = root.join(relative);
+    let candidates = if decoded.is_empty() || decoded.ends_with('/') {
+        vec![base.join("index.html")]
+    } else {
+        vec![
+            base.clone(),
+            base.with_extension("html"),
+            base.join("index.html"),
+        ]
+    };
+    candidates.into_iter().find(|p| p.is_file())
+}
+
+async fn serve_file(State(site): State<Arc<PreviewSite>>, uri: Uri) -> Response {
+    let (path, status) = match resolve(&site.root, uri.path()) {
+        Some(path) => (path, StatusCode::OK),
+        None => match resolve(&site.root, "404.html") {
+            Some(path) => (path, StatusCode::NOT_FOUND),
+            None => return (StatusCode::NOT_FOUND, "Not found").into_response(),
+        },
+    };
+
+    let bytes = match tokio::fs::read(&path).await {
+        Ok(bytes) => bytes,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let mime = mime_guess::from_path(&path).first_or_octet_stream();
+
+    let body = if site.live_reload && mime.essence_str() == "text/html" {
+        let html = String::from_utf8_lossy(&bytes);
+        let html = match html.rfind("</body>") {
+            Some(i) => format!("{}{}{}", &html[..i], LIVE_RELOAD_SCRIPT, &html[i..]),
+            None => format!("{}{}", html, LIVE_RELOAD_SCRIPT),
+        };
+        Body::from(html)
+    } else {
+        Body::from(bytes)
+    };
+
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, mime.as_ref())
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(body)
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
@@ -0,0 +1,83 @@
+/*
+ * Recently opened projects, persisted in <app data>/recent_projects.json.
+ *
+ * Updated each time the editor reports a project (set_current_project),
+ * shown in File > Open Recent and returned to the dashboard so it can
+ * offer "continue where you left off".
+ */
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+const MAX_RECENT: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentProject {
+    pub website_id: String,
+    pub name: String,
+    /// Unix timestamp (seconds) of the last time the project was opened.
+    pub opened_at: u64,
+}
+
+fn recent_path(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|d| d.join("recent_projects.json"))
+}
+
+/// Most recent first.
+pub fn list(app: &tauri::AppHandle) -> Vec<RecentProject> {
+    recent_path(app)
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, projects: &[RecentProject]) {
+    let Some(path) = recent_path(app) else { return };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    match serde_json::to_string_pretty(projects) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                tracing::warn!("Failed to save recent projects: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to encode recent projects: {}", e),
+    }
+}
+
+/// Move a project to the top of the list.
+pub fn record(app: &tauri::AppHandle, website_id: &str, name: &str) {
+    let mut projects = list(app);
+    projects.retain(|p| p.website_id != website_id);
+    projects.insert(
+        0,
+        RecentProject {
+            website_id: website_id.to_string(),
+            name: name.to_string(),
+            opened_at: crate::unix_time().as_secs(),
+        },
+    );
+    projects.truncate(MAX_RECENT);
+    save(app, &projects);
+}
+
+/// Forget a project (e.g. after it was deleted).
+pub fn remove(app: &tauri::AppHandle, website_id: &str) {
+    let mut projects = list(app);
+    let before = projects.len();
+    projects.retain(|p| p.website_id != website_id);
+    if projects.len() != before {
+        save(app, &projects);
+    }
+}
+
+pub fn clear(app: &tauri::AppHandle) {
+    save(app, &[]);
+}
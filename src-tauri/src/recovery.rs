@@ -0,0 +1,183 @@
+/*
+ * Crash recovery journal, in <app data>/recovery/<website_id>.json.
+ *
+ * While a project has unsaved changes, the bridge periodically sends the
+ * editor's project data (write_recovery command). The entry is removed
+ * when the project is saved, or when the user closes it without saving.
+ * Entries left over at launch mean Silex or the webview crashed: the user
+ * is offered to restore (written back through the server API) or discard
+ * each of them.
+ */
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryEntry {
+    pub website_id: String,
+    pub website_name: String,
+    /// Unix timestamp (seconds) of the last write.
+    pub saved_at: u64,
+    /// Project data from editor.getProjectData().
+    pub data: serde_json::Value,
+}
+
+fn recovery_dir(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|d| d.join("recovery"))
+}
+
+fn entry_path(app: &tauri::AppHandle, website_id: &str) -> Result<PathBuf, String> {
+    crate::websites::validate_website_id(website_id)?;
+    recovery_dir(app)
+        .map(|d| d.join(format!("{}.json", website_id)))
+        .ok_or_else(|| "Failed to resolve app data dir".to_string())
+}
+
+/// Write (replace) the journal entry of a project.
+pub fn write(app: &tauri::AppHandle, website_id: &str, website_name: &str, data: serde_json::Value) -> Result<(), String> {
+    let path = entry_path(app, website_id)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create recovery dir: {}", e))?;
+    }
+    let entry = RecoveryEntry {
+        website_id: website_id.to_string(),
+        website_name: website_name.to_string(),
+        saved_at: crate::unix_time().as_secs(),
+        data,
+    };
+    let json = serde_json::to_vec(&entry).map_err(|e| format!("Failed to encode recovery data: {}", e))?;
+    // Write then rename, so a crash mid-write never leaves a truncated entry
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write recovery data: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to write recovery data: {}", e))
+}
+
+/// Remove temp files of writes interrupted by a crash or exit.
+pub fn remove_temp_files(app: &tauri::AppHandle) {
+    let Some(entries) = recovery_dir(app).and_then(|d| std::fs::read_dir(d).ok()) else { return };
+    for entry in entries.flatten() {
+        if entry.path().extension().is_some_and(|e| e == "tmp") {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Remove a project's journal entry, if any.
+pub fn clear(app: &tauri::AppHandle, website_id: &str) {
+    if let Ok(path) = entry_path(app, website_id) {
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                tracing::warn!("Failed to remove recovery data for {}: {}", website_id, e);
+            }
+        }
+    }
+}
+
+/// Leftover entries, skipping those older than the saved website.
+pub fn list(app: &tauri::AppHandle) -> Vec<RecoveryEntry> {
+    let Some(dir) = recovery_dir(app) else { return Vec::new() };
+    let Ok(files) = std::fs::read_dir(&dir) else { return Vec::new() };
+    let storage = crate::storage_path(app);
+    files
+        .flatten()
+        .filter(|f| f.path().extension().is_some_and(|e| e == "json"))
+        .filter_map(|f| {
+            let entry = std::fs::read(f.path())
+                .ok()
+                .and_then(|raw| serde_json::from_slice::<RecoveryEntry>(&raw).ok());
+            let Some(entry) = entry else {
+                tracing::warn!("Ignoring unreadable recovery file {}", f.path().display());
+                return None;
+            };
+            let site_dir = storage.join(&entry.website_id);
+            let saved_on_disk = std::fs::metadata(site_dir.join("website.json"))
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs());
+            let stale = !site_dir.is_dir() || saved_on_disk.is_some_and(|t| t > entry.saved_at);
+            if stale {
+                clear(app, &entry.website_id);
+                return None;
+            }
+            Some(entry)
+        })
+        .collect()
+}
+
+/// Write recovered project data back to the website through the server API.
+async fn restore(app: &tauri::AppHandle, entry: &RecoveryEntry) -> Result<(), String> {
+    let url = format!(
+        "{}/api/website?websiteId={}&connectorId=fs-storage",
+        crate::base_url(app),
+        url::form_urlencoded::byte_serialize(entry.website_id.as_bytes()).collect::<String>()
+    );
+    let resp = reqwest::Client::new()
+        .post(&url)
+        .header("Content-Type", "application/json")
+        .body(entry.data.to_string())
+        .send()
+        .await
+        .map_err(|e| format!("Error restoring website: {}", e))?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(format!("Error restoring website ({}): {}", status, body));
+    }
+    clear(app, &entry.website_id);
+    tracing::info!("Restored unsaved changes of website {}", entry.website_id);
+    Ok(())
+}
+
+/// At launch, offer to restore or discard each leftover entry, one dialog
+/// at a time. "Decide Later" keeps the entry for the next launch.
+pub fn offer_restore(app: &tauri::AppHandle) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult};
+
+    let entries = list(app);
+    if entries.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    // Blocking dialogs must not run on the main thread
+    std::thread::spawn(move || {
+        for entry in entries {
+            let minutes_ago = crate::unix_time().as_secs().saturating_sub(entry.saved_at) / 60;
+            let result = app
+                .dialog()
+                .message(format!(
+                    "Silex closed before changes to \"{}\" were saved ({} minutes ago).\n\n\
+                     Restore the unsaved changes?",
+                    entry.website_name, minutes_ago
+                ))
+                .title("Recover Unsaved Changes")
+                .kind(MessageDialogKind::Warning)
+                .buttons(MessageDialogButtons::YesNoCancelCustom(
+                    "Restore".into(),
+                    "Discard".into(),
+                    "Decide Later".into(),
+                ))
+                .blocking_show_with_result();
+            let choice = match result {
+                MessageDialogResult::Custom(s) => s,
+                MessageDialogResult::Yes => "Restore".into(),
+                MessageDialogResult::No => "Discard".into(),
+                _ => String::new(),
+            };
+            match choice.as_str() {
+                "Restore" => {
+                    let result = tauri::async_runtime::block_on(restore(&app, &entry))
+                        .and_then(|_| crate::launch::open_website(&app, &entry.website_id));
+                    if let Err(e) = result {
+                        crate::menu::show_error(&app, "Recover Unsaved Changes", e);
+                    }
+                }
+                "Discard" => clear(&app, &entry.website_id),
+                _ => {}
+            }
+        }
+    });
+}
@@ -0,0 +1,549 @@
+/*
+ * Application settings, in <app data>/settings.json.
+ *
+ * The file carries a schema version; older files are migrated when
+ * loaded (version 0 is "no settings file": values are imported from the
+ * files used before, telemetry_consent, window_state.json and tray.json).
+ *
+ * Some settings can be overridden by environment variables, which win
+ * over the file and are shown read-only in the settings window:
+ *   SILEX_DATA_PATH         website storage folder
+ *   SILEX_MCP_PORT          MCP HTTP server port
+ *   SILEX_UPDATE_ENDPOINT   update manifest URL
+ *   SILEX_GLITCHTIP_DSN     error reporting DSN
+ *   SILEX_GLITCHTIP_ENV     error reporting environment
+ *   RUST_LOG                log filter
+ *
+ * The file is watched while the app runs: edits made outside of the app
+ * are applied like changes made in the settings window.
+ */
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+
+use crate::telemetry::{TelemetryConsent, TelemetryRecording};
+
+/// Current schema version of settings.json.
+pub const SCHEMA_VERSION: u64 = 2;
+
+pub const DEFAULT_MCP_PORT: u16 = 6807;
+pub const DEFAULT_LOG_FILTER: &str = "silex_server=info,silex_desktop=info";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub version: u64,
+    /// None until the user answered the consent dialog.
+    pub telemetry: Option<TelemetryConsent>,
+    /// Telemetry inspector: record events locally.
+    pub telemetry_recording: TelemetryRecording,
+    /// Error reporting DSN (self-hosted GlitchTip), None for the built-in one.
+    pub glitchtip_dsn: Option<String>,
+    /// Error reporting environment, None for production (development in
+    /// debug builds).
+    pub glitchtip_environment: Option<String>,
+    /// Website storage folder, None for <app data>/storage.
+    pub data_path: Option<String>,
+    pub mcp_enabled: bool,
+    pub mcp_port: u16,
+    pub check_for_updates: bool,
+    /// Update manifest URL, None for the one in tauri.conf.json.
+    pub update_endpoint: Option<String>,
+    pub log_filter: String,
+    pub reopen_last_project: bool,
+    pub keep_running_in_tray: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            telemetry: None,
+            telemetry_recording: TelemetryRecording::Off,
+            glitchtip_dsn: None,
+            glitchtip_environment: None,
+            data_path: None,
+            mcp_enabled: true,
+            mcp_port: DEFAULT_MCP_PORT,
+            check_for_updates: true,
+            update_endpoint: None,
+            log_filter: DEFAULT_LOG_FILTER.to_string(),
+            reopen_last_project: false,
+            keep_running_in_tray: false,
+        }
+    }
+}
+
+/// Values set by environment variables, read once at startup (the app sets
+/// SILEX_DATA_PATH itself for silex-server later on).
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvOverrides {
+    pub data_path: Option<String>,
+    pub mcp_port: Option<u16>,
+    pub update_endpoint: Option<String>,
+    pub log_filter: Option<String>,
+    pub glitchtip_dsn: Option<String>,
+    pub glitchtip_environment: Option<String>,
+}
+
+impl EnvOverrides {
+    fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        Self {
+            data_path: var("SILEX_DATA_PATH"),
+            mcp_port: var("SILEX_MCP_PORT").and_then(|p| p.parse().ok()),
+            update_endpoint: var("SILEX_UPDATE_ENDPOINT"),
+            log_filter: var("RUST_LOG"),
+            glitchtip_dsn: var("SILEX_GLITCHTIP_DSN"),
+            glitchtip_environment: var("SILEX_GLITCHTIP_ENV"),
+        }
+    }
+}
+
+/// How often the settings file is checked for outside edits.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Mutex<Settings>,
+    overrides: EnvOverrides,
+    /// Modification time of the file when last read or written.
+    modified: Mutex<Option<SystemTime>>,
+}
+
+fn settings_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("settings.json")
+}
+
+/// Migrate settings JSON from its version to `SCHEMA_VERSION`, one step at a time.
+fn migrate(mut value: Value, data_dir: &Path) -> Value {
+    if !value.is_object() {
+        value = Value::Object(Default::default());
+    }
+    let mut version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+    while version < SCHEMA_VERSION {
+        // One step per schema version
+        match version {
+            0 => migrate_legacy_files(&mut value, data_dir),
+            1 => migrate_consent_categories(&mut value),
+            _ => {}
+        }
+        version += 1;
+        value["version"] = version.into();
+        tracing::info!("Migrated settings to version {}", version);
+    }
+    value
+}
+
+/// Version 0 to 1: import the settings kept in separate files before.
+fn migrate_legacy_files(value: &mut Value, data_dir: &Path) {
+    let read_json = |name: &str| {
+        std::fs::read_to_string(data_dir.join(name))
+            .ok()
+            .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+    };
+    if let Ok(consent) = std::fs::read_to_string(data_dir.join("telemetry_consent")) {
+        value["telemetryConsent"] = (consent.trim() == "true").into();
+    }
+    if let Some(reopen) = read_json("window_state.json").and_then(|v| v.get("reopenLastProject").cloned()) {
+        value["reopenLastProject"] = reopen;
+    }
+    if let Some(keep) = read_json("tray.json").and_then(|v| v.get("keepRunning").cloned()) {
+        value["keepRunningInTray"] = keep;
+    }
+}
+
+/// Version 1 to 2: the single telemetry answer applies to every category.
+fn migrate_consent_categories(value: &mut Value) {
+    let Some(object) = value.as_object_mut() else { return };
+    if let Some(granted) = object.remove("telemetryConsent").and_then(|v| v.as_bool()) {
+        let consent = serde_json::to_value(TelemetryConsent::all(granted)).unwrap_or_default();
+        object.insert("telemetry".into(), consent);
+    }
+}
+
+/// Last modification time of settings.json, to notice outside edits.
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn write_file(path: &Path, settings: &Settings) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(settings).map_err(|e| format!("Failed to encode settings: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to save settings: {}", e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+/// Read the settings file, migrating it (and saving the result) if it has
+/// an older schema version.
+fn read(data_dir: &Path) -> Settings {
+    let path = settings_path(data_dir);
+    let raw = match std::fs::read_to_string(&path) {
+        Ok(raw) => match serde_json::from_str::<Value>(&raw) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                // Keep the broken file for inspection, start from defaults
+                tracing::warn!("Unreadable settings file, using defaults: {}", e);
+                let _ = std::fs::rename(&path, path.with_extension("json.bak"));
+                None
+            }
+        },
+        Err(_) => None,
+    };
+    let version = raw.as_ref().and_then(|v| v.get("version")).and_then(Value::as_u64).unwrap_or(0);
+    if version > SCHEMA_VERSION {
+        tracing::warn!("Settings were written by a newer Silex (version {}), unknown values are ignored", version);
+    }
+    let value = migrate(raw.unwrap_or(Value::Null), data_dir);
+    let settings: Settings = serde_json::from_value(value).unwrap_or_default();
+    if version < SCHEMA_VERSION {
+        match write_file(&path, &settings) {
+            Ok(()) if version == 0 => {
+                // Now part of settings.json
+                let _ = std::fs::remove_file(data_dir.join("telemetry_consent"));
+                let _ = std::fs::remove_file(data_dir.join("tray.json"));
+            }
+            Ok(()) => {}
+            Err(e) => tracing::warn!("{}", e),
+        }
+    }
+    settings
+}
+
+/// Load (and migrate) the settings. Called before Tauri starts, since
+/// logging and error tracking depend on them.
+pub fn load(data_dir: &Path) -> SettingsStore {
+    let path = settings_path(data_dir);
+    let settings = read(data_dir);
+    crate::telemetry::cache_settings(&settings);
+    SettingsStore {
+        modified: Mutex::new(modified(&path)),
+        path,
+        settings: Mutex::new(settings),
+        overrides: EnvOverrides::from_env(),
+    }
+}
+
+impl SettingsStore {
+    /// Settings as saved in the file.
+    pub fn stored(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Settings in effect: the file, overridden by environment variables.
+    pub fn effective(&self) -> Settings {
+        self.with_overrides(self.stored())
+    }
+
+    fn with_overrides(&self, mut settings: Settings) -> Settings {
+        let env = &self.overrides;
+        if let Some(path) = &env.data_path {
+            settings.data_path = Some(path.clone());
+        }
+        if let Some(port) = env.mcp_port {
+            settings.mcp_port = port;
+        }
+        if let Some(endpoint) = &env.update_endpoint {
+            settings.update_endpoint = Some(endpoint.clone());
+        }
+        if let Some(filter) = &env.log_filter {
+            settings.log_filter = filter.clone();
+        }
+        if let Some(dsn) = &env.glitchtip_dsn {
+            settings.glitchtip_dsn = Some(dsn.clone());
+        }
+        if let Some(environment) = &env.glitchtip_environment {
+            settings.glitchtip_environment = Some(environment.clone());
+        }
+        settings
+    }
+
+    pub fn overrides(&self) -> &EnvOverrides {
+        &self.overrides
+    }
+
+    /// Change the settings and save them.
+    pub fn update(&self, change: impl FnOnce(&mut Settings)) -> Result<Settings, String> {
+        let mut settings = self.settings.lock().unwrap();
+        let mut updated = settings.clone();
+        change(&mut updated);
+        updated.version = SCHEMA_VERSION;
+        write_file(&self.path, &updated)?;
+        *self.modified.lock().unwrap() = modified(&self.path);
+        crate::telemetry::cache_settings(&updated);
+        *settings = updated.clone();
+        Ok(updated)
+    }
+
+    /// Reload the file if it was changed outside of the app. Returns the
+    /// settings in effect before and after, if they changed.
+    fn reload(&self) -> Option<(Settings, Settings)> {
+        let mut settings = self.settings.lock().unwrap();
+        let current = modified(&self.path);
+        {
+            let mut last = self.modified.lock().unwrap();
+            if current.is_none() || current == *last {
+                return None;
+            }
+            *last = current;
+        }
+        let raw = std::fs::read_to_string(&self.path).ok()?;
+        let reloaded: Settings = match serde_json::from_str(&raw) {
+            Ok(reloaded) => reloaded,
+            Err(e) => {
+                // Probably saved halfway, the next change will be picked up
+                tracing::warn!("Ignoring invalid settings file: {}", e);
+                return None;
+            }
+        };
+        if reloaded == *settings {
+            return None;
+        }
+        let before = std::mem::replace(&mut *settings, reloaded);
+        crate::telemetry::cache_settings(&settings);
+        drop(settings);
+        Some((self.with_overrides(before), self.effective()))
+    }
+}
+
+/// Remove the temp file of an interrupted save (on exit).
+pub fn remove_temp_file(app: &tauri::AppHandle) {
+    let store = app.state::<SettingsStore>();
+    let _saving = store.settings.lock().unwrap();
+    let _ = std::fs::remove_file(store.path.with_extension("json.tmp"));
+}
+
+/// Settings in effect.
+pub fn get(app: &tauri::AppHandle) -> Settings {
+    app.state::<SettingsStore>().effective()
+}
+
+/// Change and save settings, logging failures (for menu and tray toggles).
+pub fn update(app: &tauri::AppHandle, change: impl FnOnce(&mut Settings)) {
+    if let Err(e) = app.state::<SettingsStore>().update(change) {
+        tracing::warn!("{}", e);
+    }
+}
+
+/// Apply edits made to the settings file outside of the app.
+pub fn watch(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(WATCH_INTERVAL).await;
+            let Some((before, after)) = app.state::<SettingsStore>().reload() else { continue };
+            tracing::info!("Settings file changed, applying it");
+            let restart = apply(&app, &before, &after);
+            if !restart.is_empty() {
+                tracing::info!("Settings applied at next launch: {}", restart.join(", "));
+            }
+        }
+    });
+}
+
+/// Check values entered in the settings window.
+pub fn validate(settings: &Settings) -> Result<(), String> {
+    if settings.mcp_port == 0 {
+        return Err("The MCP port must be between 1 and 65535".into());
+    }
+    if let Some(path) = &settings.data_path {
+        if !Path::new(path).is_absolute() {
+            return Err(format!("The storage folder must be an absolute path: {}", path));
+        }
+    }
+    if let Some(endpoint) = &settings.update_endpoint {
+        let url = url::Url::parse(endpoint).map_err(|e| format!("Invalid update URL {}: {}", endpoint, e))?;
+        if url.scheme() != "https" {
+            return Err("The update URL must use https".into());
+        }
+    }
+    if let Some(dsn) = &settings.glitchtip_dsn {
+        crate::telemetry::validate_dsn(dsn)?;
+    }
+    if settings.log_filter.trim().is_empty() {
+        return Err("The log filter can't be empty".into());
+    }
+    tracing_subscriber::EnvFilter::try_new(&settings.log_filter)
+        .map_err(|e| format!("Invalid log filter {}: {}", settings.log_filter, e))?;
+    Ok(())
+}
+
+/// What the settings window shows.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsView {
+    pub settings: Settings,
+    pub overrides: EnvOverrides,
+}
+
+/// Apply changed settings to the running app. Returns the names of those
+/// that only take effect at next launch.
+pub fn apply(app: &tauri::AppHandle, before: &Settings, after: &Settings) -> Vec<&'static str> {
+    crate::menu::apply_settings(app, after);
+    crate::telemetry::apply(&before.telemetry.unwrap_or_default(), &after.telemetry.unwrap_or_default());
+    crate::tray::apply_settings(app, after);
+    if before.mcp_enabled != after.mcp_enabled {
+        crate::mcp::set_enabled(app, after.mcp_enabled);
+    }
+    let mut restart = Vec::new();
+    if before.data_path != after.data_path {
+        restart.push("Storage folder");
+    }
+    if before.mcp_port != after.mcp_port {
+        restart.push("MCP port");
+    }
+    if before.log_filter != after.log_filter {
+        restart.push("Log filter");
+    }
+    if before.glitchtip_dsn != after.glitchtip_dsn || before.glitchtip_environment != after.glitchtip_environment {
+        restart.push("Error reporting");
+    }
+    // Without a DSN, recording only runs with a placeholder set at launch
+    if before.telemetry_recording != after.telemetry_recording && crate::telemetry::configured_dsn(after).is_none() {
+        restart.push("Telemetry recording");
+    }
+    if before.check_for_updates != after.check_for_updates || before.update_endpoint != after.update_endpoint {
+        restart.push("Updates");
+    }
+    restart
+}
+
+/// Open the settings window, or focus it.
+pub fn show_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("settings") {
+        let _ = window.set_focus();
+        return;
+    }
+    let result = tauri::WebviewWindowBuilder::new(app, "settings", tauri::WebviewUrl::App("settings.html".into()))
+        .title("Settings")
+        .inner_size(520.0, 620.0)
+        .resizable(false)
+        .center()
+        .build();
+    if let Err(e) = result {
+        crate::menu::show_error(app, "Settings", e.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Empty folder standing for the app data dir.
+    fn data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("silex-settings-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn saved(dir: &Path) -> Value {
+        serde_json::from_str(&std::fs::read_to_string(settings_path(dir)).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn version_0_imports_legacy_files() {
+        let dir = data_dir("v0");
+        std::fs::write(dir.join("telemetry_consent"), "true\n").unwrap();
+        std::fs::write(dir.join("window_state.json"), r#"{"reopenLastProject":true,"windows":{}}"#).unwrap();
+        std::fs::write(dir.join("tray.json"), r#"{"keepRunning":true}"#).unwrap();
+
+        let settings = read(&dir);
+        assert_eq!(settings.telemetry, Some(TelemetryConsent::all(true)));
+        assert!(settings.reopen_last_project);
+        assert!(settings.keep_running_in_tray);
+        assert_eq!(settings.version, SCHEMA_VERSION);
+        assert_eq!(saved(&dir)["version"], SCHEMA_VERSION);
+        // Imported files are removed, window geometry is still used
+        assert!(!dir.join("telemetry_consent").exists());
+        assert!(!dir.join("tray.json").exists());
+        assert!(dir.join("window_state.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn version_0_without_legacy_files_is_unanswered() {
+        let dir = data_dir("v0-empty");
+        let settings = read(&dir);
+        assert_eq!(settings, Settings::default());
+        assert!(settings_path(&dir).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn version_1_consent_applies_to_every_category() {
+        for granted in [true, false] {
+            let dir = data_dir(&format!("v1-{}", granted));
+            let v1 = serde_json::json!({ "version": 1, "telemetryConsent": granted, "mcpPort": 7000, "keepRunningInTray": true });
+            std::fs::write(settings_path(&dir), v1.to_string()).unwrap();
+
+            let settings = read(&dir);
+            assert_eq!(settings.telemetry, Some(TelemetryConsent::all(granted)));
+            assert_eq!(settings.mcp_port, 7000);
+            assert!(settings.keep_running_in_tray);
+            let saved = saved(&dir);
+            assert_eq!(saved["version"], SCHEMA_VERSION);
+            assert!(saved.get("telemetryConsent").is_none());
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+    }
+
+    #[test]
+    fn unparseable_file_is_kept_aside() {
+        let dir = data_dir("broken");
+        std::fs::write(settings_path(&dir), "{\"version\": 2, \"mcpPort\":").unwrap();
+
+        let settings = read(&dir);
+        assert_eq!(settings, Settings::default());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("settings.json.bak")).unwrap(),
+            "{\"version\": 2, \"mcpPort\":"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn newer_version_is_read_and_left_as_is() {
+        let dir = data_dir("newer");
+        let newer = r#"{"version":99,"mcpPort":7001,"reopenLastProject":true,"somethingNew":{"a":1}}"#;
+        std::fs::write(settings_path(&dir), newer).unwrap();
+
+        let settings = read(&dir);
+        assert_eq!(settings.mcp_port, 7001);
+        assert!(settings.reopen_last_project);
+        assert_eq!(std::fs::read_to_string(settings_path(&dir)).unwrap(), newer);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn environment_overrides_the_file() {
+        let dir = data_dir("env");
+        let stored = Settings { mcp_port: 7002, log_filter: "warn".into(), ..Default::default() };
+        let store = SettingsStore {
+            path: settings_path(&dir),
+            settings: Mutex::new(stored.clone()),
+            overrides: EnvOverrides {
+                mcp_port: Some(9000),
+                data_path: Some("/srv/silex".into()),
+                ..Default::default()
+            },
+            modified: Mutex::new(None),
+        };
+
+        let effective = store.effective();
+        assert_eq!(effective.mcp_port, 9000);
+        assert_eq!(effective.data_path.as_deref(), Some("/srv/silex"));
+        assert_eq!(effective.log_filter, "warn");
+        assert_eq!(store.stored(), stored);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
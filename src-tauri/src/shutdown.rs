@@ -0,0 +1,152 @@
+/*
+ * Graceful shutdown.
+ *
+ * When Silex exits (or restarts after an update), before the process ends:
+ *   1. MCP clients get a log notification that Silex is shutting down,
+ *      all at once and within the drain deadline
+ *   2. pending evals fail right away with "app shutting down", so tool
+ *      calls waiting on the editor answer instead of hanging
+ *   3. MCP HTTP sessions are closed, and the shared cancellation token
+ *      stops silex-server and the MCP server: they stop accepting
+ *      connections and finish in-flight requests (saves included), for
+ *      up to DRAIN_DEADLINE
+ *   4. temp files of interrupted writes are removed
+ */
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rmcp::{Peer, RoleServer};
+use tauri::async_runtime::JoinHandle;
+use tauri::Manager;
+use tokio_util::sync::CancellationToken;
+
+use crate::mcp::PendingEvals;
+
+/// How long in-flight requests get to finish.
+const DRAIN_DEADLINE: Duration = Duration::from_secs(5);
+/// How long MCP sessions get to receive the shutdown notification.
+const NOTIFY_TIMEOUT: Duration = Duration::from_millis(500);
+/// MCP sessions notified on shutdown, the most recent ones.
+const MAX_PEERS: usize = 64;
+
+/// Answer of evals still pending at shutdown (same shape as the bridge's).
+pub const SHUTTING_DOWN: &str = r#"{"success":false,"error":"app shutting down"}"#;
+
+pub struct Shutdown {
+    token: CancellationToken,
+    pending_evals: PendingEvals,
+    /// Server tasks to wait for.
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+    peers: Mutex<Vec<Peer<RoleServer>>>,
+    done: AtomicBool,
+}
+
+impl Shutdown {
+    pub fn new(pending_evals: PendingEvals) -> Self {
+        Self {
+            token: CancellationToken::new(),
+            pending_evals,
+            tasks: Mutex::new(Vec::new()),
+            peers: Mutex::new(Vec::new()),
+            done: AtomicBool::new(false),
+        }
+    }
+
+    /// Cancelled when the app shuts down (servers stop on it).
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Wait for a server task on shutdown.
+    pub fn track(&self, task: JoinHandle<()>) {
+        self.tasks.lock().unwrap().push(task);
+    }
+
+    /// Notify an MCP session on shutdown.
+    pub fn add_peer(&self, peer: Peer<RoleServer>) {
+        let mut peers = self.peers.lock().unwrap();
+        if peers.len() == MAX_PEERS {
+            peers.remove(0);
+        }
+        peers.push(peer);
+    }
+
+    fn fail_pending_evals(&self) {
+        let pending: Vec<_> = self.pending_evals.lock().unwrap().drain().collect();
+        if !pending.is_empty() {
+            tracing::info!("Failing {} pending evals", pending.len());
+        }
+        for (_, tx) in pending {
+            let _ = tx.send(SHUTTING_DOWN.to_string());
+        }
+    }
+}
+
+/// Notify all sessions at once: slow or gone clients get NOTIFY_TIMEOUT in
+/// total, never more than is left before `deadline`.
+async fn notify_peers(peers: Vec<Peer<RoleServer>>, deadline: Instant) {
+    use rmcp::model::{LoggingLevel, LoggingMessageNotificationParam};
+
+    let mut notifications = tokio::task::JoinSet::new();
+    for peer in peers {
+        notifications.spawn(async move {
+            let notification = LoggingMessageNotificationParam {
+                level: LoggingLevel::Warning,
+                logger: Some("silex".into()),
+                data: serde_json::json!("Silex is shutting down, this session will close."),
+            };
+            // Sessions that are already gone just fail
+            let _ = peer.notify_logging_message(notification).await;
+        });
+    }
+    let timeout = NOTIFY_TIMEOUT.min(deadline.saturating_duration_since(Instant::now()));
+    let all_sent = async { while notifications.join_next().await.is_some() {} };
+    if tokio::time::timeout(timeout, all_sent).await.is_err() {
+        // Dropping the set aborts the notifications still pending
+        tracing::debug!("{} MCP sessions not notified in time", notifications.len());
+    }
+}
+
+/// Shut down the servers and pending work. Runs once, later calls return
+/// right away.
+pub async fn shutdown(app: &tauri::AppHandle) {
+    let Some(state) = app.try_state::<Shutdown>() else { return };
+    if state.done.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    tracing::info!("Shutting down");
+    let deadline = Instant::now() + DRAIN_DEADLINE;
+
+    let peers = std::mem::take(&mut *state.peers.lock().unwrap());
+    notify_peers(peers, deadline).await;
+    // Cancel first, so that no new eval starts once pending ones failed
+    state.token.cancel();
+    state.fail_pending_evals();
+    if let Some(http) = app.try_state::<crate::mcp::McpHttp>() {
+        http.shutdown().await;
+    }
+
+    let tasks = std::mem::take(&mut *state.tasks.lock().unwrap());
+    for task in tasks {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if tokio::time::timeout(remaining, task).await.is_err() {
+            tracing::warn!("Servers still busy after {}s, exiting anyway", DRAIN_DEADLINE.as_secs());
+            break;
+        }
+    }
+
+    crate::recovery::remove_temp_files(app);
+    crate::settings::remove_temp_file(app);
+    tracing::info!("Shutdown complete");
+}
+
+/// `shutdown` from the event loop (RunEvent::Exit).
+pub fn run(app: &tauri::AppHandle) {
+    tauri::async_runtime::block_on(shutdown(app));
+}
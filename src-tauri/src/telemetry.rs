@@ -0,0 +1,606 @@
+/*
+ * Telemetry consent, by category:
+ *   crash reports   error events (Rust and webview)
+ *   performance     traces (startup, project open/save/publish)
+ *   sessions        usage sessions (user count, crash-free rate) and the
+ *                   app_started event
+ *
+ * Consent is stored in the settings (None until the user answered the
+ * first-launch dialog) and can be changed at any time from the settings
+ * window. Changes apply right away: Sentry hooks read the current consent
+ * (webview events go through `filter_webview_event`), and sessions are
+ * started or ended. The hooks run for every event and transaction (each
+ * MCP tool call), so they read a copy kept in memory, updated whenever the
+ * settings are loaded, saved or edited outside of the app.
+ *
+ * Every event and breadcrumb is scrubbed before it leaves the app: home
+ * directories, file paths and website names are replaced by placeholders.
+ *
+ * Reports go to the DSN set in the settings (or SILEX_GLITCHTIP_DSN), by
+ * default the one built in with GLITCHTIP_DSN, so self-hosted GlitchTip
+ * instances work with release builds.
+ *
+ * Telemetry inspector: events can also be recorded locally, in
+ * <app data>/telemetry/events.jsonl, to check exactly what is sent.
+ * "Record" records and sends, "record only" never sends anything (and
+ * records what would be sent, whatever the consent, even in builds
+ * without a DSN).
+ */
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+
+/// Tag on events that belong to the usage category rather than crash reports.
+pub const USAGE_TAG: &str = "usage";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TelemetryConsent {
+    pub crash_reports: bool,
+    pub performance: bool,
+    pub sessions: bool,
+}
+
+impl TelemetryConsent {
+    /// The same answer for every category (first-launch dialog).
+    pub fn all(granted: bool) -> Self {
+        Self {
+            crash_reports: granted,
+            performance: granted,
+            sessions: granted,
+        }
+    }
+
+    /// Whether an event may be sent: usage events need the sessions
+    /// category, everything else is a crash report.
+    pub fn allows_event(&self, event: &sentry::protocol::Event) -> bool {
+        if event.tags.get("category").map(String::as_str) == Some(USAGE_TAG) {
+            self.sessions
+        } else {
+            self.crash_reports
+        }
+    }
+}
+
+/// Local recording of telemetry (inspector).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TelemetryRecording {
+    #[default]
+    Off,
+    /// Record what is sent.
+    Record,
+    /// Record what would be sent, send nothing.
+    RecordOnly,
+}
+
+/// DSN used when recording only without a configured DSN, so that events
+/// go through the whole pipeline. Nothing is ever sent to it.
+const INSPECTOR_DSN: &str = "https://inspector@localhost/0";
+
+/// Check a DSN entered in the settings or environment.
+pub fn validate_dsn(dsn: &str) -> Result<(), String> {
+    dsn.parse::<sentry::types::Dsn>()
+        .map(|_| ())
+        .map_err(|e| format!("Invalid error reporting DSN {}: {}", dsn, e))
+}
+
+/// Where reports go: the configured DSN if valid, else the built-in one.
+pub fn configured_dsn(settings: &crate::settings::Settings) -> Option<String> {
+    settings
+        .glitchtip_dsn
+        .clone()
+        .filter(|dsn| validate_dsn(dsn).is_ok())
+        .or_else(|| option_env!("GLITCHTIP_DSN").map(String::from))
+}
+
+/// DSN for Sentry clients (Rust and webview), if telemetry can run at all.
+pub fn dsn(settings: &crate::settings::Settings) -> Option<String> {
+    configured_dsn(settings).or_else(|| {
+        (settings.telemetry_recording == TelemetryRecording::RecordOnly).then(|| INSPECTOR_DSN.to_string())
+    })
+}
+
+/// Environment reported with events.
+pub fn environment(settings: &crate::settings::Settings) -> String {
+    settings.glitchtip_environment.clone().unwrap_or_else(|| {
+        if cfg!(debug_assertions) { "development" } else { "production" }.to_string()
+    })
+}
+
+/// Sentry browser bundle, served to the webview instead of the CDN (copied
+/// from node_modules by build.rs, empty if it wasn't installed).
+const BROWSER_BUNDLE: &str = include_str!(concat!(env!("OUT_DIR"), "/sentry.bundle.tracing.min.js"));
+
+/// What the webview needs to set up its Sentry client.
+#[derive(Serialize)]
+pub struct BrowserClient {
+    bundle: &'static str,
+    release: &'static str,
+    environment: String,
+}
+
+pub fn browser_client(settings: &crate::settings::Settings) -> Option<BrowserClient> {
+    if BROWSER_BUNDLE.is_empty() {
+        return None;
+    }
+    Some(BrowserClient {
+        bundle: BROWSER_BUNDLE,
+        release: env!("CARGO_PKG_VERSION"),
+        environment: environment(settings),
+    })
+}
+
+/// Current consent, all categories off until the user answered.
+pub fn consent(app: &tauri::AppHandle) -> TelemetryConsent {
+    crate::settings::get(app).telemetry.unwrap_or_default()
+}
+
+/// Start or end the Sentry session to match the sessions category.
+pub fn apply_sessions(consent: &TelemetryConsent) {
+    if consent.sessions {
+        sentry::start_session();
+    } else {
+        sentry::end_session();
+    }
+}
+
+/// Apply a consent change to the running app.
+pub fn apply(before: &TelemetryConsent, after: &TelemetryConsent) {
+    if before.sessions != after.sessions {
+        apply_sessions(after);
+    }
+    if before != after {
+        tracing::info!(
+            "Telemetry consent: crash reports {}, performance {}, sessions {}",
+            after.crash_reports,
+            after.performance,
+            after.sessions
+        );
+    }
+}
+
+/// Save and apply a new consent.
+pub fn set_consent(app: &tauri::AppHandle, consent: TelemetryConsent) -> Result<(), String> {
+    let before = self::consent(app);
+    app.state::<crate::settings::SettingsStore>()
+        .update(|s| s.telemetry = Some(consent))?;
+    apply(&before, &consent);
+    Ok(())
+}
+
+/// Ask for consent on first launch (non-blocking). The answer applies to
+/// every category; they can be changed one by one in Settings.
+pub fn prompt_consent(app: &tauri::AppHandle) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    let app_handle = app.clone();
+    app.dialog()
+        .message(
+            "Help improve Silex by sending anonymous crash reports and basic usage data?\n\n\
+             No personal data or website content is ever collected.\n\
+             You can change this later in Settings.",
+        )
+        .title("Telemetry")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Yes, help improve Silex".into(),
+            "No thanks".into(),
+        ))
+        .show(move |accepted| {
+            if let Err(e) = set_consent(&app_handle, TelemetryConsent::all(accepted)) {
+                tracing::warn!("{}", e);
+            }
+        });
+}
+
+// ==========================================================================
+// Sentry hooks
+// ==========================================================================
+
+/// What the Sentry hooks need from the settings.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct HookState {
+    pub consent: TelemetryConsent,
+    pub recording: TelemetryRecording,
+}
+
+static HOOK_STATE: RwLock<HookState> = RwLock::new(HookState {
+    consent: TelemetryConsent {
+        crash_reports: false,
+        performance: false,
+        sessions: false,
+    },
+    recording: TelemetryRecording::Off,
+});
+
+/// Update the hooks' copy of the settings (on load and on every change).
+pub fn cache_settings(settings: &crate::settings::Settings) {
+    *HOOK_STATE.write().unwrap() = HookState {
+        consent: settings.telemetry.unwrap_or_default(),
+        recording: settings.telemetry_recording,
+    };
+}
+
+pub fn hook_state() -> HookState {
+    *HOOK_STATE.read().unwrap()
+}
+
+/// Serializes tests that change the hooks' state, and restores it after.
+#[cfg(test)]
+pub(crate) struct HookStateLock {
+    saved: HookState,
+    _guard: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+impl HookStateLock {
+    pub(crate) fn acquire() -> Self {
+        static LOCK: Mutex<()> = Mutex::new(());
+        // A failed test must not fail the others
+        let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        HookStateLock { saved: hook_state(), _guard: guard }
+    }
+}
+
+#[cfg(test)]
+impl Drop for HookStateLock {
+    fn drop(&mut self) {
+        *HOOK_STATE.write().unwrap_or_else(|e| e.into_inner()) = self.saved;
+    }
+}
+
+/// `before_send`: drop events without consent, scrub the others.
+pub fn before_send(event: sentry::protocol::Event<'static>) -> Option<sentry::protocol::Event<'static>> {
+    let state = hook_state();
+    // Recording only: keep everything, the transport sends nothing
+    let keep = state.consent.allows_event(&event) || state.recording == TelemetryRecording::RecordOnly;
+    if keep { scrub_event(event) } else { None }
+}
+
+/// `traces_sampler`: 100% of transactions (volume is low for a desktop
+/// app), but only with consent to performance data.
+pub fn sample_trace(_ctx: &sentry::TransactionContext) -> f32 {
+    let state = hook_state();
+    let keep = state.consent.performance || state.recording == TelemetryRecording::RecordOnly;
+    if keep { 1.0 } else { 0.0 }
+}
+
+// ==========================================================================
+// Scrubbing
+// ==========================================================================
+
+/// Stack frame keys left as is: source locations, set at build time.
+const UNSCRUBBED_FRAME_KEYS: &[&str] = &["abs_path", "filename"];
+
+/// Shorter website names would match too many unrelated words.
+const MIN_TERM_LENGTH: usize = 3;
+
+fn sensitive_terms() -> &'static Mutex<Vec<String>> {
+    static TERMS: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+    TERMS.get_or_init(Default::default)
+}
+
+/// Remember a website name, to scrub it from telemetry.
+pub fn add_sensitive_term(term: &str) {
+    let term = term.trim();
+    if term.chars().count() < MIN_TERM_LENGTH {
+        return;
+    }
+    let mut terms = sensitive_terms().lock().unwrap();
+    if !terms.iter().any(|t| t == term) {
+        terms.push(term.to_string());
+        // Longest first, so a name containing another one is replaced whole
+        terms.sort_by_key(|t| std::cmp::Reverse(t.len()));
+    }
+}
+
+/// Absolute paths (Unix, Windows, file:// URLs), at the start of a string
+/// or after a separator so URL paths are left alone.
+fn path_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r#"(^|[\s"'(=\[,])(?:file://)?(?:[A-Za-z]:[\\/]|~?/)[^\s"'<>|?*,;)\]]+"#)
+            .expect("valid path pattern")
+    })
+}
+
+/// Replace home directories, website names and file paths in a string.
+pub fn scrub_str(text: &str) -> String {
+    let mut text = text.to_string();
+    if let Some(home) = dirs::home_dir() {
+        let home = home.to_string_lossy().into_owned();
+        if !home.is_empty() && home != "/" {
+            text = text.replace(&home, "~").replace(&home.replace('\\', "/"), "~");
+        }
+    }
+    for term in sensitive_terms().lock().unwrap().iter() {
+        text = text.replace(term.as_str(), "<website>");
+    }
+    path_pattern().replace_all(&text, "${1}<path>").into_owned()
+}
+
+/// Scrub every string of a JSON value, and drop the host name.
+pub fn scrub_value(value: &mut Value) {
+    scrub_value_in(value, false);
+}
+
+/// `in_frames`: `value` is a stack trace's `frames` list, or one of its
+/// frames. Other `filename` keys (attachments, contexts...) are scrubbed.
+fn scrub_value_in(value: &mut Value, in_frames: bool) {
+    match value {
+        Value::String(s) => *s = scrub_str(s),
+        Value::Array(items) => items.iter_mut().for_each(|item| scrub_value_in(item, in_frames)),
+        Value::Object(map) => {
+            map.remove("server_name");
+            for (key, item) in map.iter_mut() {
+                if !(in_frames && UNSCRUBBED_FRAME_KEYS.contains(&key.as_str())) {
+                    scrub_value_in(item, key == "frames");
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Scrub through the JSON form, dropping what can't be scrubbed.
+fn scrub<T: Serialize + serde::de::DeserializeOwned>(item: T) -> Option<T> {
+    let mut value = serde_json::to_value(&item).ok()?;
+    scrub_value(&mut value);
+    serde_json::from_value(value).ok()
+}
+
+pub fn scrub_event(event: sentry::protocol::Event<'static>) -> Option<sentry::protocol::Event<'static>> {
+    scrub(event)
+}
+
+pub fn scrub_breadcrumb(breadcrumb: sentry::Breadcrumb) -> Option<sentry::Breadcrumb> {
+    scrub(breadcrumb)
+}
+
+// ==========================================================================
+// Local recording (inspector)
+// ==========================================================================
+
+/// Recorded events are rotated past this size.
+const MAX_RECORDING_SIZE: u64 = 5 * 1024 * 1024;
+
+fn recording_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("telemetry").join("events.jsonl")
+}
+
+/// Append one item to the recording.
+fn record(data_dir: &Path, source: &str, kind: &str, sent: bool, payload: Value) {
+    // Events and webview calls may record concurrently
+    static WRITE: Mutex<()> = Mutex::new(());
+    let _guard = WRITE.lock().unwrap();
+
+    let path = recording_path(data_dir);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if std::fs::metadata(&path).is_ok_and(|m| m.len() > MAX_RECORDING_SIZE) {
+        let _ = std::fs::rename(&path, path.with_extension("jsonl.1"));
+    }
+    let line = serde_json::json!({
+        "recordedAt": crate::unix_time().as_secs(),
+        "source": source,
+        "type": kind,
+        "sent": sent,
+        "payload": payload,
+    });
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut f| writeln!(f, "{}", line));
+    if let Err(e) = result {
+        tracing::warn!("Failed to record telemetry: {}", e);
+    }
+}
+
+/// Recorded items, most recent first.
+pub fn list_recorded(data_dir: &Path, limit: usize) -> Vec<Value> {
+    let Ok(file) = std::fs::File::open(recording_path(data_dir)) else { return Vec::new() };
+    let mut items: Vec<Value> = std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect();
+    items.reverse();
+    items.truncate(limit);
+    items
+}
+
+pub fn clear_recorded(data_dir: &Path) {
+    let path = recording_path(data_dir);
+    let _ = std::fs::remove_file(path.with_extension("jsonl.1"));
+    if let Err(e) = std::fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("Failed to clear recorded telemetry: {}", e);
+        }
+    }
+}
+
+/// Sentry transport recording envelopes locally, then sending them through
+/// the default transport unless recording only.
+struct RecordingTransport {
+    data_dir: PathBuf,
+    inner: Arc<dyn sentry::Transport>,
+}
+
+impl sentry::Transport for RecordingTransport {
+    fn send_envelope(&self, envelope: sentry::Envelope) {
+        use sentry::protocol::EnvelopeItem;
+
+        // Transactions and sessions don't go through before_send: scrub
+        // them here, and record and send the same scrubbed envelope
+        let envelope = scrub_envelope(&envelope);
+        let recording = hook_state().recording;
+        if recording != TelemetryRecording::Off {
+            for item in envelope.items() {
+                let (kind, payload) = match item {
+                    EnvelopeItem::Event(event) => ("event", serde_json::to_value(event)),
+                    EnvelopeItem::Transaction(transaction) => ("transaction", serde_json::to_value(transaction)),
+                    EnvelopeItem::SessionUpdate(session) => ("session", serde_json::to_value(session)),
+                    EnvelopeItem::SessionAggregates(sessions) => ("sessions", serde_json::to_value(sessions)),
+                    _ => continue,
+                };
+                if let Ok(payload) = payload {
+                    record(&self.data_dir, "rust", kind, recording == TelemetryRecording::Record, payload);
+                }
+            }
+        }
+        if recording != TelemetryRecording::RecordOnly {
+            self.inner.send_envelope(envelope);
+        }
+    }
+
+    fn flush(&self, timeout: std::time::Duration) -> bool {
+        self.inner.flush(timeout)
+    }
+
+    fn shutdown(&self, timeout: std::time::Duration) -> bool {
+        self.inner.shutdown(timeout)
+    }
+}
+
+/// Copy of an envelope with its events, transactions and sessions scrubbed.
+/// Items that can't be scrubbed are dropped, other items are kept.
+fn scrub_envelope(envelope: &sentry::Envelope) -> sentry::Envelope {
+    use sentry::protocol::EnvelopeItem;
+
+    let mut scrubbed = sentry::Envelope::new();
+    for item in envelope.items() {
+        let item = match item {
+            EnvelopeItem::Event(event) => scrub(event.clone()).map(EnvelopeItem::from),
+            EnvelopeItem::Transaction(transaction) => scrub(transaction.clone()).map(EnvelopeItem::from),
+            EnvelopeItem::SessionUpdate(session) => scrub(session.clone()).map(EnvelopeItem::from),
+            EnvelopeItem::SessionAggregates(sessions) => scrub(sessions.clone()).map(EnvelopeItem::from),
+            other => Some(other.clone()),
+        };
+        if let Some(item) = item {
+            scrubbed.add_item(item);
+        }
+    }
+    scrubbed
+}
+
+/// Transport for `sentry::ClientOptions`: the default one, with recording.
+pub fn transport_factory(data_dir: PathBuf) -> Arc<dyn sentry::TransportFactory> {
+    Arc::new(move |options: &sentry::ClientOptions| -> Arc<dyn sentry::Transport> {
+        use sentry::TransportFactory;
+
+        Arc::new(RecordingTransport {
+            data_dir: data_dir.clone(),
+            inner: sentry::transports::DefaultTransportFactory.create_transport(options),
+        })
+    })
+}
+
+/// Decide what happens to an event of the webview Sentry client: scrubbed,
+/// recorded if enabled, and returned only if it may be sent.
+pub fn filter_webview_event(app: &tauri::AppHandle, kind: &str, mut event: Value) -> Option<Value> {
+    let state = hook_state();
+    let allowed = match kind {
+        "transaction" => state.consent.performance,
+        _ => state.consent.crash_reports,
+    };
+    scrub_value(&mut event);
+    let data_dir = app.path().app_data_dir().ok()?;
+    match state.recording {
+        TelemetryRecording::Off => allowed.then_some(event),
+        TelemetryRecording::Record => {
+            if allowed {
+                record(&data_dir, "webview", kind, true, event.clone());
+            }
+            allowed.then_some(event)
+        }
+        TelemetryRecording::RecordOnly => {
+            record(&data_dir, "webview", kind, false, event);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoopTransport;
+
+    impl sentry::Transport for NoopTransport {
+        fn send_envelope(&self, _envelope: sentry::Envelope) {}
+    }
+
+    /// MCP tool calls start a transaction each: sampling must only read the
+    /// consent cached by the settings store, never the settings file.
+    #[test]
+    fn tool_transactions_use_cached_consent() {
+        let _state = HookStateLock::acquire();
+        let client = sentry::Client::from(sentry::ClientOptions {
+            dsn: INSPECTOR_DSN.parse().ok(),
+            traces_sampler: Some(Arc::new(sample_trace)),
+            transport: Some(Arc::new(|_: &sentry::ClientOptions| -> Arc<dyn sentry::Transport> {
+                Arc::new(NoopTransport)
+            })),
+            ..Default::default()
+        });
+        let hub = Arc::new(sentry::Hub::new(Some(Arc::new(client)), Default::default()));
+        let sampled = || {
+            sentry::Hub::run(hub.clone(), || {
+                let tx = crate::mcp::SilexMcp::start_tool_transaction("website", "list");
+                let sampled = tx.is_sampled();
+                tx.finish();
+                sampled
+            })
+        };
+
+        let dir = std::env::temp_dir().join(format!("silex-telemetry-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = crate::settings::load(&dir);
+        assert!(!sampled());
+
+        store
+            .update(|s| s.telemetry = Some(TelemetryConsent { performance: true, ..TelemetryConsent::all(false) }))
+            .unwrap();
+        assert!(sampled());
+
+        // Without a data dir, or with an unreadable file, the cached consent applies
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(sampled());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("settings.json"), "not json").unwrap();
+        assert!(sampled());
+
+        // Consent changes apply to the next call
+        store.update(|s| s.telemetry = Some(TelemetryConsent::all(false))).unwrap();
+        assert!(!sampled());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn only_stack_frame_locations_are_kept() {
+        let mut event = serde_json::json!({
+            "exception": {"values": [{"stacktrace": {"frames": [{
+                "filename": "/build/silex/src/main.rs",
+                "abs_path": "/build/silex/src/main.rs",
+                "vars": {"filename": "/srv/sites/index.html"},
+            }]}}]},
+            "extra": {"filename": "/srv/sites/index.html"},
+        });
+        scrub_value(&mut event);
+
+        let frame = &event["exception"]["values"][0]["stacktrace"]["frames"][0];
+        assert_eq!(frame["filename"], "/build/silex/src/main.rs");
+        assert_eq!(frame["abs_path"], "/build/silex/src/main.rs");
+        assert_eq!(frame["vars"]["filename"], "<path>");
+        assert_eq!(event["extra"]["filename"], "<path>");
+    }
+}
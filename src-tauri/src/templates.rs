@@ -0,0 +1,327 @@
+/*
+ * Website templates for Silex Desktop.
+ *
+ * Built-in starters are bundled with the app as resources (templates/).
+ * User templates are saved from existing sites into <app data>/templates.
+ * Each template is a folder:
+ *   template.json  metadata (name, description, creation date)
+ *   preview.png    thumbnail taken from an editor screenshot (optional)
+ *   website/       copy of the site's storage folder
+ */
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+/// Placeholder written in place of the source website id, so asset URLs
+/// point at the new site once the template is instantiated.
+const WEBSITE_ID_PLACEHOLDER: &str = "__SILEX_TEMPLATE_WEBSITE_ID__";
+
+// ==================
+// Metadata
+// ==================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateMeta {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Unix timestamp (seconds), unset for built-in templates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default)]
+    pub builtin: bool,
+    /// Absolute path of the preview thumbnail, filled when listing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+}
+
+fn user_templates_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|d| d.join("templates"))
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))
+}
+
+fn builtin_templates_dir(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path().resource_dir().ok().map(|d| d.join("templates"))
+}
+
+fn read_template(dir: &Path, builtin: bool) -> Option<TemplateMeta> {
+    let raw = std::fs::read_to_string(dir.join("template.json")).ok()?;
+    let mut meta: TemplateMeta = serde_json::from_str(&raw).ok()?;
+    meta.id = dir.file_name()?.to_string_lossy().into_owned();
+    meta.builtin = builtin;
+    let preview = dir.join("preview.png");
+    meta.preview = preview
+        .is_file()
+        .then(|| preview.to_string_lossy().into_owned());
+    Some(meta)
+}
+
+fn read_templates_in(dir: &Path, builtin: bool) -> Vec<TemplateMeta> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut templates: Vec<TemplateMeta> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| read_template(&e.path(), builtin))
+        .collect();
+    templates.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    templates
+}
+
+/// Built-in templates first, then user templates.
+pub fn list_templates(app: &tauri::AppHandle) -> Vec<TemplateMeta> {
+    let mut templates = builtin_templates_dir(app)
+        .map(|d| read_templates_in(&d, true))
+        .unwrap_or_default();
+    if let Ok(dir) = user_templates_dir(app) {
+        templates.extend(read_templates_in(&dir, false));
+    }
+    templates
+}
+
+/// Resolve a template id to its folder. User templates shadow built-ins.
+fn find_template(app: &tauri::AppHandle, template_id: &str) -> Result<PathBuf, String> {
+    if template_id.is_empty() || template_id.contains(['/', '\\']) || template_id.starts_with('.') {
+        return Err(format!("Invalid template id '{}'", template_id));
+    }
+    let candidates = [
+        user_templates_dir(app).ok(),
+        builtin_templates_dir(app),
+    ];
+    candidates
+        .into_iter()
+        .flatten()
+        .map(|d| d.join(template_id))
+        .find(|d| d.join("template.json").is_file())
+        .ok_or_else(|| format!("Template '{}' not found", template_id))
+}
+
+// ==================
+// File helpers
+// ==================
+
+/// Create an empty website through the server API. Returns its id.
+pub(crate) async fn create_website(base_url: &str, name: Option<&str>) -> Result<String, String> {
+    let url = format!("{}/api/website?connectorId=fs-storage", base_url);
+    let body = match name {
+        Some(name) => serde_json::json!({ "name": name }),
+        None => serde_json::json!({}),
+    };
+    let resp = reqwest::Client::new()
+        .put(&url)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .map_err(|e| format!("Error creating website: {}", e))?;
+    let status = resp.status();
+    let response_body = resp
+        .text()
+        .await
+        .map_err(|e| format!("Error reading response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("Error creating website ({}): {}", status, response_body));
+    }
+    serde_json::from_str::<serde_json::Value>(&response_body)
+        .ok()
+        .and_then(|v| v.get("websiteId").and_then(|id| id.as_str().map(String::from)))
+        .ok_or_else(|| format!("Unexpected response from server: {}", response_body))
+}
+
+pub(crate) fn copy_dir(src: &Path, dst: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Point the id fields of the JSON files at the top of a site folder
+/// (website.json, page files) at another website: `websiteId` values, and
+/// `websiteId=` parameters of asset URLs. Other text containing the id
+/// (names, page content) is left alone.
+///
+/// Files are edited as text, keeping their formatting, and only written
+/// when something changed.
+pub(crate) fn rewrite_website_id(dir: &Path, from: &str, to: &str) -> std::io::Result<()> {
+    if from.is_empty() || to.is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "empty website id"));
+    }
+    // Ids as they appear inside JSON strings
+    let json_inner = |id: &str| {
+        let quoted = serde_json::Value::String(id.to_string()).to_string();
+        quoted[1..quoted.len() - 1].to_string()
+    };
+    let (from, to) = (regex::escape(&json_inner(from)), json_inner(to));
+    // A quote can't appear unescaped inside a string, so this only matches fields
+    let field = regex::Regex::new(&format!(r#"("websiteId"\s*:\s*)"{}""#, from)).expect("valid website id pattern");
+    // The id ends the parameter: followed by another one, a fragment, or
+    // the end of the URL (closing or escaped quote, space, parenthesis)
+    let param = regex::Regex::new(&format!(r#"websiteId={}([&#'"\s)\\])"#, from)).expect("valid website id pattern");
+
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if !path.extension().is_some_and(|e| e == "json") {
+            continue;
+        }
+        let content = std::fs::read_to_string(&path)?;
+        if serde_json::from_str::<serde_json::Value>(&content).is_err() {
+            tracing::warn!("Skipping unreadable site file {}", path.display());
+            continue;
+        }
+        let rewritten = field.replace_all(&content, |caps: &regex::Captures| format!("{}\"{}\"", &caps[1], to));
+        let rewritten = param.replace_all(&rewritten, |caps: &regex::Captures| format!("websiteId={}{}", to, &caps[1]));
+        if rewritten != content {
+            std::fs::write(&path, rewritten.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete a website through the server API (cleanup after a failed creation).
+pub(crate) async fn delete_website(base_url: &str, website_id: &str) {
+    let url = format!(
+        "{}/api/website?websiteId={}&connectorId=fs-storage",
+        base_url,
+        url::form_urlencoded::byte_serialize(website_id.as_bytes()).collect::<String>()
+    );
+    match reqwest::Client::new().delete(&url).send().await {
+        Ok(resp) if resp.status().is_success() => tracing::info!("Removed incomplete website {}", website_id),
+        Ok(resp) => tracing::warn!("Failed to remove incomplete website {}: {}", website_id, resp.status()),
+        Err(e) => tracing::warn!("Failed to remove incomplete website {}: {}", website_id, e),
+    }
+}
+
+// ==================
+// Save / instantiate
+// ==================
+
+fn slugify(name: &str) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() { "template".into() } else { slug }
+}
+
+/// Save an existing website as a user template.
+/// `preview_png` is the thumbnail, usually an editor screenshot.
+pub fn save_as_template(
+    app: &tauri::AppHandle,
+    website_id: &str,
+    name: &str,
+    description: &str,
+    preview_png: Option<&[u8]>,
+) -> Result<TemplateMeta, String> {
+    let site_dir = crate::websites::site_dir(app, website_id)?;
+    if !site_dir.is_dir() {
+        return Err(format!("Website '{}' not found in storage", website_id));
+    }
+
+    let templates_dir = user_templates_dir(app)?;
+    let mut id = slugify(name);
+    if templates_dir.join(&id).exists() {
+        id = format!("{}-{}", id, crate::unix_time().as_secs());
+    }
+    let dir = templates_dir.join(&id);
+
+    let website_dir = dir.join("website");
+    copy_dir(&site_dir, &website_dir)
+        .map_err(|e| format!("Failed to copy website files: {}", e))?;
+    // The template gets its own name on instantiation
+    let _ = std::fs::remove_file(website_dir.join("meta.json"));
+    rewrite_website_id(&website_dir, website_id, WEBSITE_ID_PLACEHOLDER)
+        .map_err(|e| format!("Failed to prepare template files: {}", e))?;
+
+    if let Some(png) = preview_png {
+        if let Err(e) = std::fs::write(dir.join("preview.png"), png) {
+            tracing::warn!("Failed to save template preview: {}", e);
+        }
+    }
+
+    let meta = TemplateMeta {
+        id: id.clone(),
+        name: name.to_string(),
+        description: description.to_string(),
+        created_at: Some(crate::unix_time().as_secs()),
+        builtin: false,
+        preview: None,
+    };
+    let json = serde_json::to_string_pretty(&meta)
+        .map_err(|e| format!("Failed to encode template metadata: {}", e))?;
+    std::fs::write(dir.join("template.json"), json)
+        .map_err(|e| format!("Failed to write template metadata: {}", e))?;
+
+    tracing::info!("Saved website {} as template '{}'", website_id, id);
+    read_template(&dir, false).ok_or_else(|| "Failed to read saved template".into())
+}
+
+/// Copy a template's files into an existing (freshly created) website.
+pub fn instantiate(app: &tauri::AppHandle, template_id: &str, website_id: &str) -> Result<(), String> {
+    let template_dir = find_template(app, template_id)?;
+    let site_dir = crate::websites::site_dir(app, website_id)?;
+
+    let src = template_dir.join("website");
+    if !src.is_dir() {
+        return Err(format!("Template '{}' has no website files", template_id));
+    }
+    for entry in std::fs::read_dir(&src).map_err(|e| format!("Failed to read template: {}", e))? {
+        let entry = entry.map_err(|e| format!("Failed to read template: {}", e))?;
+        // Keep the meta.json written by the server (name, dates)
+        if entry.file_name() == "meta.json" {
+            continue;
+        }
+        let target = site_dir.join(entry.file_name());
+        let copied = if entry.path().is_dir() {
+            copy_dir(&entry.path(), &target)
+        } else {
+            std::fs::copy(entry.path(), &target).map(|_| ())
+        };
+        copied.map_err(|e| format!("Failed to copy template files: {}", e))?;
+    }
+    rewrite_website_id(&site_dir, WEBSITE_ID_PLACEHOLDER, website_id)
+        .map_err(|e| format!("Failed to finalize website files: {}", e))?;
+
+    tracing::info!("Created website {} from template '{}'", website_id, template_id);
+    Ok(())
+}
+
+/// Create a new website through the server API and fill it from a template.
+/// Returns the new website id.
+pub async fn create_from_template(
+    app: &tauri::AppHandle,
+    base_url: &str,
+    template_id: &str,
+    name: Option<&str>,
+) -> Result<String, String> {
+    // Fail before creating anything if the template doesn't exist
+    find_template(app, template_id)?;
+
+    let website_id = create_website(base_url, name).await?;
+    if let Err(e) = instantiate(app, template_id, &website_id) {
+        // Don't leave an empty site behind
+        delete_website(base_url, &website_id).await;
+        return Err(e);
+    }
+    Ok(website_id)
+}
@@ -0,0 +1,235 @@
+/*
+ * System tray icon.
+ *
+ * Shows whether the embedded silex-server and the MCP HTTP server are
+ * running (ports, connected MCP sessions) and gives quick access to the
+ * dashboard, recent projects, the MCP endpoint and Quit.
+ *
+ * "Keep Running in Tray" (a setting) keeps the app alive when the last
+ * editor window is closed, so agents stay connected to the MCP server.
+ * Quit always exits.
+ */
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{TrayIcon, TrayIconBuilder};
+use tauri::{Manager, Wry};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::mcp::McpHttp;
+
+/// How often the status lines and tooltip are refreshed.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct TrayState {
+    icon: TrayIcon<Wry>,
+    server_status: MenuItem<Wry>,
+    mcp_status: MenuItem<Wry>,
+    mcp_enabled: CheckMenuItem<Wry>,
+    keep_running_item: CheckMenuItem<Wry>,
+    recent: Submenu<Wry>,
+    /// Set by Quit, so closing the last window exits even in tray mode.
+    quitting: AtomicBool,
+}
+
+fn item(app: &tauri::AppHandle, id: &str, text: &str, enabled: bool) -> tauri::Result<MenuItem<Wry>> {
+    MenuItem::with_id(app, id, text, enabled, None::<&str>)
+}
+
+/// Create the tray icon and start refreshing its status.
+pub fn build(app: &tauri::AppHandle) -> tauri::Result<()> {
+    let server_status = item(app, "tray-server-status", "Silex server: starting", false)?;
+    let mcp_status = item(app, "tray-mcp-status", "MCP server: starting", false)?;
+    let recent = Submenu::new(app, "Recent Projects", true)?;
+    crate::menu::fill_recent(app, &recent)?;
+    // Over stdio the agent owns the MCP transport, there's nothing to toggle
+    let http = app.try_state::<McpHttp>().is_some();
+    let settings = crate::settings::get(app);
+    let mcp_enabled = CheckMenuItem::with_id(
+        app,
+        "tray-mcp",
+        "Enable MCP Server",
+        http,
+        http && settings.mcp_enabled,
+        None::<&str>,
+    )?;
+    let keep_running_item = CheckMenuItem::with_id(
+        app,
+        "tray-keep-running",
+        "Keep Running in Tray",
+        true,
+        settings.keep_running_in_tray,
+        None::<&str>,
+    )?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &server_status,
+            &mcp_status,
+            &PredefinedMenuItem::separator(app)?,
+            &item(app, "tray-dashboard", "Open Dashboard", true)?,
+            &recent,
+            &PredefinedMenuItem::separator(app)?,
+            &item(app, "tray-copy-url", "Copy MCP URL", http)?,
+            &item(app, "tray-copy-config", "Copy MCP Config", http)?,
+            &mcp_enabled,
+            &PredefinedMenuItem::separator(app)?,
+            &keep_running_item,
+            &item(app, "tray-quit", "Quit Silex", true)?,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id("silex")
+        .tooltip("Silex")
+        .menu(&menu)
+        .show_menu_on_left_click(true);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone().to_owned());
+    }
+    let icon = builder.build(app)?;
+
+    app.manage(TrayState {
+        icon,
+        server_status,
+        mcp_status,
+        mcp_enabled,
+        keep_running_item,
+        recent,
+        quitting: AtomicBool::new(false),
+    });
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            refresh(&app).await;
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+        }
+    });
+    Ok(())
+}
+
+/// Update the status lines and tooltip.
+pub async fn refresh(app: &tauri::AppHandle) {
+    let Some(tray) = app.try_state::<TrayState>() else { return };
+    let server = match app.try_state::<crate::ServerInfo>() {
+        Some(info) => format!("Silex server: port {}", info.port),
+        None => "Silex server: starting".to_string(),
+    };
+    let mcp = match app.try_state::<McpHttp>() {
+        Some(http) => match http.addr() {
+            Some(addr) => {
+                let sessions = http.session_count().await;
+                format!(
+                    "MCP server: port {}, {} session{}",
+                    addr.port(),
+                    sessions,
+                    if sessions == 1 { "" } else { "s" }
+                )
+            }
+            None => "MCP server: stopped".to_string(),
+        },
+        None => "MCP server: stdio".to_string(),
+    };
+    let _ = tray.server_status.set_text(&server);
+    let _ = tray.mcp_status.set_text(&mcp);
+    let _ = tray.icon.set_tooltip(Some(format!("Silex\n{}\n{}", server, mcp)));
+}
+
+/// Rebuild the Recent Projects submenu (with File > Open Recent).
+pub fn refresh_recent(app: &tauri::AppHandle) {
+    let Some(tray) = app.try_state::<TrayState>() else { return };
+    if let Err(e) = crate::menu::fill_recent(app, &tray.recent) {
+        tracing::warn!("Failed to update tray recent projects: {}", e);
+    }
+}
+
+/// Sync the check items with settings changed in the settings window.
+pub fn apply_settings(app: &tauri::AppHandle, settings: &crate::settings::Settings) {
+    let Some(tray) = app.try_state::<TrayState>() else { return };
+    let _ = tray.keep_running_item.set_checked(settings.keep_running_in_tray);
+    if app.try_state::<McpHttp>().is_some() {
+        let _ = tray.mcp_enabled.set_checked(settings.mcp_enabled);
+    }
+}
+
+/// Whether the app should stay alive once the last window is closed.
+pub fn keeps_running(app: &tauri::AppHandle) -> bool {
+    app.try_state::<TrayState>().is_some_and(|t| {
+        crate::settings::get(app).keep_running_in_tray && !t.quitting.load(Ordering::Relaxed)
+    })
+}
+
+/// Mark the app as quitting: closing the last window exits, tray mode or not.
+pub fn set_quitting(app: &tauri::AppHandle) {
+    if let Some(tray) = app.try_state::<TrayState>() {
+        tray.quitting.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Focus a window showing the dashboard, or open one.
+fn open_dashboard(app: &tauri::AppHandle) {
+    let state = app.state::<crate::AppState>();
+    let dashboard = crate::editor_windows(app)
+        .into_iter()
+        .find(|w| state.project(w.label()).website_id.is_none());
+    let result = match dashboard {
+        Some(window) => {
+            let _ = window.unminimize();
+            let _ = window.show();
+            window.set_focus().map_err(|e| e.to_string())
+        }
+        None => crate::open_editor_window(app, None).map(|_| ()),
+    };
+    if let Err(e) = result {
+        crate::menu::show_error(app, "Open Dashboard", e);
+    }
+}
+
+fn copy(app: &tauri::AppHandle, text: String) {
+    if let Err(e) = app.clipboard().write_text(text) {
+        crate::menu::show_error(app, "Copy", e.to_string());
+    }
+}
+
+/// MCP client configuration for the HTTP endpoint.
+fn mcp_config(url: &str) -> String {
+    let config = serde_json::json!({
+        "mcpServers": {
+            "silex": { "type": "http", "url": url }
+        }
+    });
+    serde_json::to_string_pretty(&config).unwrap_or_default()
+}
+
+pub fn on_menu_event(app: &tauri::AppHandle, id: &str) {
+    match id {
+        "tray-dashboard" => open_dashboard(app),
+        "tray-copy-url" => {
+            if let Some(http) = app.try_state::<McpHttp>() {
+                copy(app, http.url());
+            }
+        }
+        "tray-copy-config" => {
+            if let Some(http) = app.try_state::<McpHttp>() {
+                copy(app, mcp_config(&http.url()));
+            }
+        }
+        // Check items toggle themselves, save their new state
+        "tray-mcp" => {
+            let Some(tray) = app.try_state::<TrayState>() else { return };
+            let enabled = tray.mcp_enabled.is_checked().unwrap_or(false);
+            crate::settings::update(app, |s| s.mcp_enabled = enabled);
+            crate::mcp::set_enabled(app, enabled);
+        }
+        "tray-keep-running" => {
+            let Some(tray) = app.try_state::<TrayState>() else { return };
+            let enabled = tray.keep_running_item.is_checked().unwrap_or(false);
+            crate::settings::update(app, |s| s.keep_running_in_tray = enabled);
+        }
+        "tray-quit" => crate::menu::quit(app),
+        _ => {}
+    }
+}
@@ -0,0 +1,272 @@
+/*
+ * Website listing helpers for the MCP website tool.
+ *
+ * The server's /api/website returns every site, unsorted. These helpers
+ * filter, sort, paginate and project that list, and compute per-site
+ * statistics from the storage folder.
+ *
+ * Website ids given by the webview, MCP clients or archives are validated
+ * here before being joined into a path.
+ */
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// ==================
+// Website ids
+// ==================
+
+/// Reject ids that would point outside the storage folder once joined to it.
+pub fn validate_website_id(website_id: &str) -> Result<(), String> {
+    if website_id.is_empty()
+        || website_id.contains(['/', '\\', ':', '\0'])
+        || website_id.starts_with('.')
+    {
+        return Err(format!("Invalid website id '{}'", website_id));
+    }
+    Ok(())
+}
+
+/// Storage folder of a website.
+pub fn site_dir(app: &tauri::AppHandle, website_id: &str) -> Result<PathBuf, String> {
+    validate_website_id(website_id)?;
+    Ok(crate::storage_path(app).join(website_id))
+}
+
+// ==================
+// List query
+// ==================
+
+/// Sort order of the website list.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Last updated first.
+    Updated,
+    /// Last created first.
+    Created,
+    /// Alphabetical.
+    Name,
+}
+
+#[derive(Debug, Default)]
+pub struct ListQuery<'a> {
+    pub query: Option<&'a str>,
+    pub sort: Option<SortOrder>,
+    pub limit: Option<usize>,
+    pub cursor: Option<&'a str>,
+    pub compact: bool,
+}
+
+impl ListQuery<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.query.is_none()
+            && self.sort.is_none()
+            && self.limit.is_none()
+            && self.cursor.is_none()
+            && !self.compact
+    }
+}
+
+/// Score a name against a query: substring matches beat fuzzy (subsequence)
+/// matches, and tighter fuzzy matches beat scattered ones. None = no match.
+fn match_score(name: &str, query: &str) -> Option<i64> {
+    let name = name.to_lowercase();
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Some(0);
+    }
+    if let Some(pos) = name.find(&query) {
+        return Some(10_000 - pos as i64);
+    }
+    let mut chars = name.char_indices();
+    let mut first = None;
+    let mut last = 0;
+    for q in query.chars() {
+        let (i, _) = chars.find(|(_, c)| *c == q)?;
+        first.get_or_insert(i);
+        last = i;
+    }
+    let span = (last - first.unwrap_or(0)) as i64;
+    Some(1_000 - span)
+}
+
+/// Dates may be numbers (ms) or ISO strings depending on the connector.
+fn date_key(site: &Value, field: &str) -> (f64, String) {
+    match site.get(field) {
+        Some(Value::Number(n)) => (n.as_f64().unwrap_or(0.0), String::new()),
+        Some(Value::String(s)) => (0.0, s.clone()),
+        _ => (0.0, String::new()),
+    }
+}
+
+fn name_of(site: &Value) -> &str {
+    site.get("name").and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// Position of a site in a sorted list: its sort key, then its id to
+/// order sites with the same key.
+#[derive(Debug, Serialize, Deserialize)]
+struct Position {
+    key: (f64, String),
+    id: String,
+}
+
+/// Where the next page starts: the position of the last site returned,
+/// so that sites added or removed in between don't shift the pages.
+/// `sort` and `filters` tie it to the list it was returned for.
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    sort: Option<SortOrder>,
+    filters: u64,
+    after: Position,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        use base64::Engine;
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json)
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        use base64::Engine;
+        let json = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+/// Hash of the filters of a list query (the name query), stored in cursors.
+fn filters_hash(q: &ListQuery) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    q.query.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn position(site: &Value, score: i64, sort: Option<SortOrder>, has_query: bool) -> Position {
+    let key = match sort {
+        Some(SortOrder::Name) => (0.0, name_of(site).to_lowercase()),
+        Some(SortOrder::Created) => date_key(site, "createdAt"),
+        Some(SortOrder::Updated) => date_key(site, "updatedAt"),
+        None if has_query => (score as f64, String::new()),
+        None => (0.0, String::new()),
+    };
+    let id = site.get("websiteId").and_then(|v| v.as_str()).unwrap_or("").to_string();
+    Position { key, id }
+}
+
+/// Order of two positions: dates and relevance descending, names ascending,
+/// then ids ascending.
+fn compare(a: &Position, b: &Position, descending: bool) -> std::cmp::Ordering {
+    let key = a
+        .key
+        .0
+        .partial_cmp(&b.key.0)
+        .unwrap_or(std::cmp::Ordering::Equal)
+        .then_with(|| a.key.1.cmp(&b.key.1));
+    let key = if descending { key.reverse() } else { key };
+    key.then_with(|| a.id.cmp(&b.id))
+}
+
+/// Apply a list query to the server's website list.
+/// Returns `{ websites, total, nextCursor }`.
+pub fn query_websites(sites: Vec<Value>, q: &ListQuery) -> Result<Value, String> {
+    let descending = q.sort != Some(SortOrder::Name);
+    let mut positioned: Vec<(Position, Value)> = sites
+        .into_iter()
+        .filter_map(|site| {
+            let score = match q.query {
+                Some(query) => match_score(name_of(&site), query)?,
+                None => 0,
+            };
+            Some((position(&site, score, q.sort, q.query.is_some()), site))
+        })
+        .collect();
+    positioned.sort_by(|(a, _), (b, _)| compare(a, b, descending));
+    let total = positioned.len();
+
+    if let Some(cursor) = q.cursor {
+        let cursor = Cursor::decode(cursor).ok_or_else(|| format!("Invalid cursor '{}'", cursor))?;
+        if cursor.sort != q.sort {
+            return Err("The cursor was returned for another sort order, list again from the first page.".into());
+        }
+        if cursor.filters != filters_hash(q) {
+            return Err("The cursor was returned for another query, list again from the first page.".into());
+        }
+        positioned.retain(|(p, _)| compare(p, &cursor.after, descending) == std::cmp::Ordering::Greater);
+    }
+    let limit = q.limit.unwrap_or(total);
+    let has_more = positioned.len() > limit;
+    positioned.truncate(limit);
+
+    let next_cursor = match positioned.last() {
+        Some((last, _)) if has_more => Some(
+            Cursor {
+                sort: q.sort,
+                filters: filters_hash(q),
+                after: Position { key: last.key.clone(), id: last.id.clone() },
+            }
+            .encode(),
+        ),
+        _ => None,
+    };
+    let websites: Vec<Value> = positioned
+        .into_iter()
+        .map(|(_, site)| {
+            if q.compact {
+                json!({
+                    "websiteId": site.get("websiteId"),
+                    "name": site.get("name"),
+                    "updatedAt": site.get("updatedAt"),
+                })
+            } else {
+                site
+            }
+        })
+        .collect();
+
+    Ok(json!({
+        "websites": websites,
+        "total": total,
+        "nextCursor": next_cursor,
+    }))
+}
+
+// ==================
+// Per-site statistics
+// ==================
+
+fn dir_stats(dir: &Path) -> (u64, u64) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (0, 0);
+    };
+    entries.flatten().fold((0, 0), |(files, bytes), entry| {
+        match entry.metadata() {
+            Ok(m) if m.is_dir() => {
+                let (f, b) = dir_stats(&entry.path());
+                (files + f, bytes + b)
+            }
+            Ok(m) => (files + 1, bytes + m.len()),
+            Err(_) => (files, bytes),
+        }
+    })
+}
+
+/// Page count, asset count and size on disk of a site's storage folder.
+pub fn website_stats(site_dir: &Path) -> Value {
+    let page_count = std::fs::read_to_string(site_dir.join("website.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+        .and_then(|v| v.get("pages").and_then(|p| p.as_array()).map(|p| p.len()));
+    let (asset_count, _) = dir_stats(&site_dir.join("assets"));
+    let (_, size_on_disk) = dir_stats(site_dir);
+
+    json!({
+        "pageCount": page_count,
+        "assetCount": asset_count,
+        "sizeOnDisk": size_on_disk,
+    })
+}
@@ -0,0 +1,198 @@
+/*
+ * Editor window geometry, persisted in <app data>/window_state.json.
+ *
+ * Position, size, maximized state and monitor are captured per window
+ * label as windows move and resize, written when a window is destroyed,
+ * and applied when a window with the same label is created again.
+ * Positions that are no longer visible (unplugged monitor, changed
+ * layout) are clamped back onto a monitor.
+ */
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Monitor, PhysicalPosition, PhysicalSize};
+
+/// Smallest restored size, so a bad entry can't produce an unusable window.
+const MIN_WIDTH: u32 = 640;
+const MIN_HEIGHT: u32 = 480;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowGeometry {
+    /// Outer position, physical pixels.
+    pub x: i32,
+    pub y: i32,
+    /// Inner size, physical pixels (size before maximizing).
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+    /// Name of the monitor the window was on.
+    #[serde(default)]
+    pub monitor: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StateFile {
+    #[serde(default)]
+    windows: HashMap<String, WindowGeometry>,
+}
+
+#[derive(Default)]
+pub struct WindowStates(Mutex<StateFile>);
+
+fn state_path(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|d| d.join("window_state.json"))
+}
+
+/// Read the saved state (call once at startup, then `app.manage` it).
+pub fn load(app: &tauri::AppHandle) -> WindowStates {
+    let file = state_path(app)
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    WindowStates(Mutex::new(file))
+}
+
+/// Write the captured state to disk (when a window is destroyed).
+pub fn save(app: &tauri::AppHandle) {
+    let Some(states) = app.try_state::<WindowStates>() else { return };
+    let Some(path) = state_path(app) else { return };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    match serde_json::to_string_pretty(&*states.0.lock().unwrap()) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                tracing::warn!("Failed to save window state: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to encode window state: {}", e),
+    }
+}
+
+/// Record a window's current geometry (on move and resize).
+pub fn capture(window: &tauri::WebviewWindow) {
+    let app = window.app_handle();
+    let Some(states) = app.try_state::<WindowStates>() else { return };
+    // Minimized windows report bogus positions
+    if window.is_minimized().unwrap_or(false) {
+        return;
+    }
+    let maximized = window.is_maximized().unwrap_or(false);
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+    let mut file = states.0.lock().unwrap();
+    let previous = file.windows.get(window.label()).cloned();
+    let geometry = match (maximized, previous) {
+        // Keep the normal bounds, to restore them when unmaximizing
+        (true, Some(previous)) => WindowGeometry { maximized: true, monitor, ..previous },
+        _ => {
+            let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else { return };
+            WindowGeometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                maximized,
+                monitor,
+            }
+        }
+    };
+    file.windows.insert(window.label().to_string(), geometry);
+}
+
+fn saved(app: &tauri::AppHandle, label: &str) -> Option<WindowGeometry> {
+    let states = app.try_state::<WindowStates>()?;
+    let file = states.0.lock().unwrap();
+    file.windows.get(label).cloned()
+}
+
+fn contains(monitor: &Monitor, x: i32, y: i32) -> bool {
+    let area = monitor.work_area();
+    x >= area.position.x
+        && y >= area.position.y
+        && x < area.position.x + area.size.width as i32
+        && y < area.position.y + area.size.height as i32
+}
+
+/// Monitor to restore a window on: the one showing its title bar, else the
+/// one it was saved on, else the primary monitor.
+fn target_monitor(app: &tauri::AppHandle, geometry: &WindowGeometry) -> Option<(Monitor, bool)> {
+    let monitors = app.available_monitors().unwrap_or_default();
+    // A point of the title bar must be visible for the user to move the window
+    let (title_x, title_y) = (geometry.x + 48, geometry.y + 16);
+    if let Some(m) = monitors.iter().find(|m| contains(m, title_x, title_y)) {
+        return Some((m.clone(), true));
+    }
+    let by_name = geometry
+        .monitor
+        .as_ref()
+        .and_then(|name| monitors.iter().find(|m| m.name() == Some(name)).cloned());
+    by_name
+        .or_else(|| app.primary_monitor().ok().flatten())
+        .or_else(|| monitors.into_iter().next())
+        .map(|m| (m, false))
+}
+
+/// Saved geometry for a label, clamped onto a visible monitor.
+fn clamped(app: &tauri::AppHandle, label: &str) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>, bool)> {
+    let geometry = saved(app, label)?;
+    let Some((monitor, visible)) = target_monitor(app, &geometry) else {
+        return Some((
+            PhysicalPosition::new(geometry.x, geometry.y),
+            PhysicalSize::new(geometry.width, geometry.height),
+            geometry.maximized,
+        ));
+    };
+    let area = monitor.work_area();
+    let width = geometry.width.clamp(MIN_WIDTH.min(area.size.width), area.size.width);
+    let height = geometry.height.clamp(MIN_HEIGHT.min(area.size.height), area.size.height);
+    let (x, y) = if visible {
+        (geometry.x, geometry.y)
+    } else {
+        tracing::info!("Window '{}' was off-screen, moving it back onto a monitor", label);
+        let max_x = area.position.x + (area.size.width - width) as i32;
+        let max_y = area.position.y + (area.size.height - height) as i32;
+        (
+            geometry.x.clamp(area.position.x, max_x),
+            geometry.y.clamp(area.position.y, max_y),
+        )
+    };
+    Some((PhysicalPosition::new(x, y), PhysicalSize::new(width, height), geometry.maximized))
+}
+
+/// Apply the saved geometry of a window's label. Returns false when there
+/// is none (first launch), so the caller can pick a default.
+pub fn restore(window: &tauri::WebviewWindow) -> bool {
+    let Some((position, size, maximized)) = clamped(window.app_handle(), window.label()) else {
+        return false;
+    };
+    let _ = window.set_size(size);
+    let _ = window.set_position(position);
+    if maximized {
+        let _ = window.maximize();
+    }
+    true
+}
+
+/// Logical position centering a window of the given logical size on the
+/// monitor the main window was saved on, if any.
+pub fn centered_on_saved_monitor(app: &tauri::AppHandle, width: f64, height: f64) -> Option<(f64, f64)> {
+    let geometry = saved(app, "main")?;
+    let (monitor, _) = target_monitor(app, &geometry)?;
+    let scale = monitor.scale_factor();
+    let area = monitor.work_area();
+    let x = area.position.x as f64 / scale + (area.size.width as f64 / scale - width) / 2.0;
+    let y = area.position.y as f64 / scale + (area.size.height as f64 / scale - height) / 2.0;
+    Some((x, y))
+}